@@ -0,0 +1,137 @@
+use crate::ast::{Ast, BlockExpr, ExprId, ExprKind, FunctionDeclaration, InterpolatedStringPart, LoopLabel, StmtId, StmtKind};
+use crate::diagnostics::DiagnosticsBagCell;
+use crate::typings::Type;
+
+/// A loop being walked, `exited` is set once a reachable `break` targets it.
+struct LoopExit {
+    label: Option<String>,
+    exited: bool,
+}
+
+/// Reachability over function bodies. Reports non-void functions whose end can be
+/// reached without a value and warns about the first statement of a block that can never run.
+pub struct ControlFlowChecker {
+    diagnostics: DiagnosticsBagCell,
+    loops: Vec<LoopExit>,
+}
+
+impl ControlFlowChecker {
+    pub fn new(diagnostics: DiagnosticsBagCell) -> Self {
+        Self { diagnostics, loops: Vec::new() }
+    }
+
+    pub fn check_function(&mut self, ast: &Ast, func_decl: &FunctionDeclaration, return_type: &Type) {
+        let diverges = self.expr_diverges(ast, func_decl.body);
+        let returns_value = !matches!(return_type, Type::Void | Type::Unresolved | Type::Error);
+        // A reachable end is fine as long as the body's trailing expression provides the value
+        if returns_value && !diverges && matches!(ast.query_expr(func_decl.body).ty, Type::Void) {
+            self.diagnostics.borrow_mut().report_not_all_code_paths_return_value(&func_decl.identifier);
+        }
+    }
+
+    /// Whether control never continues past `stmt`.
+    fn stmt_diverges(&mut self, ast: &Ast, stmt: StmtId) -> bool {
+        match &ast.query_stmt(stmt).kind {
+            StmtKind::Expr(expr) => self.expr_diverges(ast, *expr),
+            StmtKind::Let(let_stmt) => self.expr_diverges(ast, let_stmt.initializer),
+            StmtKind::Return(return_stmt) => {
+                if let Some(value) = return_stmt.return_value {
+                    self.expr_diverges(ast, value);
+                }
+                true
+            }
+            StmtKind::Break(break_stmt) => {
+                if let Some(value) = break_stmt.value {
+                    self.expr_diverges(ast, value);
+                }
+                let target = match &break_stmt.label {
+                    Some(label) => self.loops.iter().rposition(|loop_exit| loop_exit.label.as_deref() == Some(label.span.literal.as_str())),
+                    None => self.loops.len().checked_sub(1),
+                };
+                if let Some(target) = target {
+                    self.loops[target].exited = true;
+                }
+                true
+            }
+            StmtKind::Continue(_) => true,
+            StmtKind::While(while_stmt) => {
+                let condition_diverges = self.expr_diverges(ast, while_stmt.condition);
+                let is_infinite = matches!(&ast.query_expr(while_stmt.condition).kind, ExprKind::Boolean(condition) if condition.value);
+                let exited = self.loop_body_exits(ast, &while_stmt.label, while_stmt.body);
+                condition_diverges || (is_infinite && !exited)
+            }
+            StmtKind::For(for_stmt) => {
+                let mut bounds_diverge = self.expr_diverges(ast, for_stmt.start);
+                if let Some(range) = &for_stmt.range {
+                    bounds_diverge |= self.expr_diverges(ast, range.end);
+                    bounds_diverge |= range.step.as_ref().is_some_and(|step| self.expr_diverges(ast, step.expr));
+                }
+                // The range or the string may be empty, so the body says nothing about what follows
+                self.loop_body_exits(ast, &for_stmt.label, for_stmt.body);
+                bounds_diverge
+            }
+            StmtKind::Error(_) => false,
+        }
+    }
+
+    /// Whether evaluating `expr` never produces a value. Every operand is walked, so
+    /// unreachable code nested anywhere in the expression is reported.
+    fn expr_diverges(&mut self, ast: &Ast, expr: ExprId) -> bool {
+        match &ast.query_expr(expr).kind {
+            ExprKind::Block(block_expr) => self.block_diverges(ast, block_expr),
+            ExprKind::If(if_expr) => {
+                let condition_diverges = self.expr_diverges(ast, if_expr.condition);
+                let then_diverges = self.expr_diverges(ast, if_expr.then_branch);
+                let else_diverges = if_expr.else_branch.as_ref().is_some_and(|else_branch| self.expr_diverges(ast, else_branch.expr));
+                condition_diverges || (then_diverges && else_diverges)
+            }
+            ExprKind::Loop(loop_expr) => !self.loop_body_exits(ast, &loop_expr.label, loop_expr.body),
+            ExprKind::Binary(binary_expr) => {
+                self.expr_diverges(ast, binary_expr.left) | self.expr_diverges(ast, binary_expr.right)
+            }
+            ExprKind::Unary(unary_expr) => self.expr_diverges(ast, unary_expr.operand),
+            ExprKind::Cast(cast_expr) => self.expr_diverges(ast, cast_expr.expr),
+            ExprKind::Index(index_expr) => {
+                self.expr_diverges(ast, index_expr.target) | self.expr_diverges(ast, index_expr.index)
+            }
+            ExprKind::Slice(slice_expr) => {
+                self.expr_diverges(ast, slice_expr.target) | self.expr_diverges(ast, slice_expr.start)
+                    | self.expr_diverges(ast, slice_expr.end)
+            }
+            ExprKind::Parenthesized(parenthesized_expr) => self.expr_diverges(ast, parenthesized_expr.expression),
+            ExprKind::Assignment(assign_expr) => self.expr_diverges(ast, assign_expr.expression),
+            ExprKind::Call(call_expr) => call_expr.arguments.iter()
+                .fold(false, |diverges, argument| self.expr_diverges(ast, *argument) | diverges),
+            ExprKind::InterpolatedString(interpolated_string) => interpolated_string.parts.iter()
+                .fold(false, |diverges, part| match part {
+                    InterpolatedStringPart::Expr(expr) => self.expr_diverges(ast, *expr) | diverges,
+                    InterpolatedStringPart::Literal(_) => diverges,
+                }),
+            ExprKind::Number(_) | ExprKind::Decimal(_) | ExprKind::Char(_) | ExprKind::String(_) | ExprKind::Boolean(_)
+            | ExprKind::Variable(_) | ExprKind::Error(_) => false,
+        }
+    }
+
+    fn block_diverges(&mut self, ast: &Ast, block_expr: &BlockExpr) -> bool {
+        for (index, stmt) in block_expr.stmts.iter().enumerate() {
+            if self.stmt_diverges(ast, *stmt) {
+                if let Some(unreachable) = block_expr.stmts.get(index + 1) {
+                    let span = ast.query_stmt(*unreachable).span(ast);
+                    self.diagnostics.borrow_mut().report_unreachable_code(&span);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Walks a loop body and returns whether a reachable `break` exits the loop.
+    fn loop_body_exits(&mut self, ast: &Ast, label: &Option<LoopLabel>, body: ExprId) -> bool {
+        self.loops.push(LoopExit {
+            label: label.as_ref().map(|label| label.name().to_string()),
+            exited: false,
+        });
+        self.expr_diverges(ast, body);
+        self.loops.pop().is_some_and(|loop_exit| loop_exit.exited)
+    }
+}
@@ -1,17 +1,20 @@
 use crate::ast::evaluator::Value as OtherValue;
 use fusion_compiler::Idx;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::string;
 use std::sync::Arc;
 
 use crate::ast;
 use crate::ast::visitor::ASTVisitor;
 use crate::ast::{
-    AssignExpr, Ast, BinOpKind, BinaryExpr, BlockExpr, BoolExpr, CallExpr, Expr,
-    FunctionDeclaration, IfExpr, ItemId, LetStmt, NumberExpr, ParenthesizedExpr, Stmt, UnOpKind,
+    AssignExpr, Ast, BinOpKind, BinaryExpr, BlockExpr, BoolExpr, BreakStmt, CallExpr, CastExpr, CharExpr, ContinueStmt, Expr, ExprId, ExprKind,
+    ForStmt, FunctionDeclaration, IfExpr, IndexExpr, InterpolatedStringExpr, InterpolatedStringPart, ItemId, LetStmt, LoopExpr, LoopLabel, NumberExpr, ParenthesizedExpr, ReturnStmt, SliceExpr, Stmt, StmtId, UnOpKind,
     UnaryExpr, VarExpr, WhileStmt,
 };
-use crate::compilation_unit::{FunctionIdx, GlobalScope, VariableIdx};
+use crate::compilation_unit::{BuiltinFunction, FunctionIdx, GlobalScope, VariableIdx};
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
+use crate::typings::{IntegerType, Type};
 use crate::text::span::TextSpan; // Add the missing import statement for the `ast` module
 
 #[derive(Debug)]
@@ -80,8 +83,12 @@ impl Frames {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
+    /// A value of a signed integer type
     Number(i64),
+    /// A value of an unsigned integer type
+    Unsigned(u64),
     Decimal(f64),
+    Char(char),
     String(Arc<String>),
     Boolean(bool),
     Function(FunctionIdx),
@@ -102,6 +109,15 @@ impl Value {
         }
     }
 
+    /// The value of any integer type, wide enough for the computations of all of them.
+    pub fn expect_integer(&self) -> i128 {
+        match self {
+            Value::Number(value) => *value as i128,
+            Value::Unsigned(value) => *value as i128,
+            _ => panic!("Expected integer value"),
+        }
+    }
+
     pub fn expect_decimal(&self) -> f64 {
         match self {
             Value::Decimal(value) => *value,
@@ -109,9 +125,16 @@ impl Value {
         }
     }
 
+    pub fn expect_char(&self) -> char {
+        match self {
+            Value::Char(value) => *value,
+            _ => panic!("Expected char value"),
+        }
+    }
+
     pub fn expect_string(&self) -> &str {
         match self {
-            Value::String(_string) => "",
+            Value::String(string) => string,
             _ => panic!("Expected string value"),
         }
     }
@@ -124,8 +147,40 @@ impl Value {
     }
 }
 
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(value) => write!(f, "{}", value),
+            Value::Unsigned(value) => write!(f, "{}", value),
+            Value::Decimal(value) => write!(f, "{}", value),
+            Value::Char(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Function(_) => write!(f, "<function>"),
+        }
+    }
+}
+
+/// An error that aborts the evaluation, pointing at `span`. Unlike the panics for values
+/// the resolver should have ruled out, these are returned by `CompilationUnit::evaluate`.
+fn runtime_error(message: String, span: TextSpan) -> Diagnostic {
+    Diagnostic::new(message, span, DiagnosticKind::Error)
+}
+
+/// A `break`, `continue` or `return` on its way to the loop or call it exits, or a
+/// runtime error on its way out of the evaluation. Statements are skipped while one
+/// is pending.
+#[derive(Debug, Clone)]
+pub enum ControlFlow {
+    Break { label: Option<String>, value: Option<Value> },
+    Continue { label: Option<String> },
+    Return { value: Option<Value> },
+    Error(Diagnostic),
+}
+
 pub struct ASTEvaluator<'a> {
     pub last_value: Option<Value>,
+    pub control_flow: Option<ControlFlow>,
     pub frames: Frames,
     pub global_scope: &'a GlobalScope,
 }
@@ -134,6 +189,7 @@ impl<'a> ASTEvaluator<'a> {
     pub fn new(global_scope: &'a GlobalScope) -> Self {
         Self {
             last_value: None,
+            control_flow: None,
             frames: Frames::new(),
             global_scope,
         }
@@ -147,6 +203,206 @@ impl<'a> ASTEvaluator<'a> {
         self.frames.pop();
     }
 
+    /// Takes the pending `break` or `continue` if it exits the loop labeled `label`,
+    /// unlabeled ones exit the innermost loop.
+    fn take_control_flow_for(&mut self, label: &Option<LoopLabel>) -> Option<ControlFlow> {
+        let target = match &self.control_flow {
+            Some(ControlFlow::Break { label, .. }) | Some(ControlFlow::Continue { label }) => label.as_deref(),
+            Some(ControlFlow::Return { .. }) | Some(ControlFlow::Error(_)) | None => return None,
+        };
+        match target {
+            Some(target) if Some(target) != label.as_ref().map(|label| label.name()) => None,
+            _ => self.control_flow.take(),
+        }
+    }
+
+    /// Evaluates a function body, the value of a `return` inside it becomes the
+    /// value of the body.
+    pub fn visit_function_body(&mut self, ast: &mut Ast, body: ExprId) {
+        self.visit_expression(ast, body);
+        match self.control_flow.take() {
+            Some(ControlFlow::Return { value }) => self.last_value = value,
+            control_flow => self.control_flow = control_flow,
+        }
+    }
+
+    /// The runtime error that stopped the evaluation, if any.
+    pub fn take_error(&mut self) -> Option<Diagnostic> {
+        match self.control_flow.take() {
+            Some(ControlFlow::Error(error)) => Some(error),
+            control_flow => {
+                self.control_flow = control_flow;
+                None
+            }
+        }
+    }
+
+    /// Evaluates `expr` and returns its value, or `None` if it raised an error or
+    /// left a `break`, `continue` or `return` pending, which the caller must then
+    /// pass on by returning.
+    fn evaluate(&mut self, ast: &mut Ast, expr: ExprId) -> Option<Value> {
+        self.visit_expression(ast, expr);
+        match self.control_flow {
+            Some(_) => None,
+            None => Some(self.expect_last_value()),
+        }
+    }
+
+    /// Makes the result of an operation the last value, or raises its error.
+    fn set_result(&mut self, result: Result<Value, Diagnostic>) {
+        match result {
+            Ok(value) => self.last_value = Some(value),
+            Err(error) => self.control_flow = Some(ControlFlow::Error(error)),
+        }
+    }
+
+    /// `value` as a value of `integer`, which it must fit into.
+    fn integer_value(value: i128, integer: IntegerType) -> Value {
+        match integer.is_signed() {
+            true => Value::Number(value as i64),
+            false => Value::Unsigned(value as u64),
+        }
+    }
+
+    /// The result of a signed operation, which is an error if it doesn't fit into `integer`.
+    fn checked_integer_value(value: i128, integer: IntegerType, span: &TextSpan) -> Result<Value, Diagnostic> {
+        if value < integer.min() || value > integer.max() {
+            return Err(runtime_error(format!("Integer overflow, the result does not fit into '{}'", Type::integer(integer)), span.clone()));
+        }
+        Ok(Self::integer_value(value, integer))
+    }
+
+    /// The integer operators with the semantics of the `<stdint.h>` types, which the
+    /// runtime functions of the C backend implement as well. Unsigned arithmetic wraps
+    /// around, signed arithmetic that overflows is an error. Shifts take the amount
+    /// modulo the width of the type and never overflow.
+    fn apply_integer_operator(operator: &BinOpKind, integer: IntegerType, left: i128, right: i128, span: &TextSpan) -> Result<Value, Diagnostic> {
+        let amount = (right & (integer.bits() as i128 - 1)) as u32;
+        let result = match operator {
+            BinOpKind::Plus => left + right,
+            BinOpKind::Minus => left - right,
+            // Only unsigned 64 bit products don't fit, and those keep their low bits
+            BinOpKind::Multiply => left.wrapping_mul(right),
+            BinOpKind::Divide if right == 0 => return Err(runtime_error("Division by zero".to_string(), span.clone())),
+            BinOpKind::Modulo if right == 0 => return Err(runtime_error("Remainder by zero".to_string(), span.clone())),
+            BinOpKind::Divide => left / right,
+            // The operands are widened, so `i64::MIN % -1` is 0 like in the C runtime
+            BinOpKind::Modulo => left % right,
+            BinOpKind::Power => return Self::integer_power(integer, left, right, span),
+            BinOpKind::BitwiseAnd => left & right,
+            BinOpKind::BitwiseOr => left | right,
+            BinOpKind::BitwiseXor => left ^ right,
+            BinOpKind::ShiftLeft => return Ok(Self::integer_value(integer.wrap(left << amount), integer)),
+            BinOpKind::ShiftRight if integer.is_signed() => left >> amount,
+            BinOpKind::ShiftRight | BinOpKind::UnsignedShiftRight => {
+                let bits = left & ((1 << integer.bits()) - 1);
+                return Ok(Self::integer_value(integer.wrap(bits >> amount), integer));
+            }
+            BinOpKind::Equals => return Ok(Value::Boolean(left == right)),
+            BinOpKind::NotEquals => return Ok(Value::Boolean(left != right)),
+            BinOpKind::LessThan => return Ok(Value::Boolean(left < right)),
+            BinOpKind::LessThanOrEqual => return Ok(Value::Boolean(left <= right)),
+            BinOpKind::GreaterThan => return Ok(Value::Boolean(left > right)),
+            BinOpKind::GreaterThanOrEqual => return Ok(Value::Boolean(left >= right)),
+            _ => panic!("Operator {:?} cannot be applied to integers", operator),
+        };
+        match integer.is_signed() {
+            true => Self::checked_integer_value(result, integer, span),
+            false => Ok(Self::integer_value(integer.wrap(result), integer)),
+        }
+    }
+
+    /// Exponentiation by squaring, signed products are checked one at a time in the same
+    /// order as `fx_pow` does, so they overflow exactly when the result does.
+    fn integer_power(integer: IntegerType, base: i128, exponent: i128, span: &TextSpan) -> Result<Value, Diagnostic> {
+        if exponent < 0 {
+            return Err(runtime_error(format!("Cannot raise an integer to the negative power {}", exponent), span.clone()));
+        }
+        let multiply = |left: i128, right: i128| match integer.is_signed() {
+            true => Self::checked_integer_value(left * right, integer, span).map(|value| value.expect_integer()),
+            false => Ok(integer.wrap(left.wrapping_mul(right))),
+        };
+        let (mut result, mut base, mut exponent) = (1, base, exponent);
+        while exponent > 1 {
+            if exponent & 1 == 1 {
+                result = multiply(result, base)?;
+            }
+            exponent /= 2;
+            base = multiply(base, base)?;
+        }
+        if exponent == 1 {
+            result = multiply(result, base)?;
+        }
+        Ok(Self::integer_value(result, integer))
+    }
+
+    /// Applies `operator` to operands of `operand_type`, `span` is where runtime errors
+    /// are reported.
+    fn apply_binary_operator(operator: &BinOpKind, operand_type: &Type, left: Value, right: Value, span: &TextSpan) -> Result<Value, Diagnostic> {
+        if let Some(integer) = operand_type.integer_type() {
+            return Self::apply_integer_operator(operator, integer, left.expect_integer(), right.expect_integer(), span);
+        }
+        Ok(match operator {
+            BinOpKind::PlusDecimal => Value::Decimal(left.expect_decimal() + right.expect_decimal()),
+            BinOpKind::MinusDecimal => Value::Decimal(left.expect_decimal() - right.expect_decimal()),
+            BinOpKind::MultiplyDecimal => Value::Decimal(left.expect_decimal() * right.expect_decimal()),
+            BinOpKind::DivideDecimal => Value::Decimal(left.expect_decimal() / right.expect_decimal()),
+            BinOpKind::PlusString => {
+                let mut string = String::with_capacity(left.expect_string().len() + right.expect_string().len());
+                string.push_str(left.expect_string());
+                string.push_str(right.expect_string());
+                Value::String(Arc::new(string))
+            }
+            BinOpKind::LessThanChar => Value::Boolean(left.expect_char() < right.expect_char()),
+            BinOpKind::LessThanOrEqualChar => Value::Boolean(left.expect_char() <= right.expect_char()),
+            BinOpKind::GreaterThanChar => Value::Boolean(left.expect_char() > right.expect_char()),
+            BinOpKind::GreaterThanOrEqualChar => Value::Boolean(left.expect_char() >= right.expect_char()),
+            // Byte order is code point order in UTF-8
+            BinOpKind::EqualsString => Value::Boolean(left.expect_string() == right.expect_string()),
+            BinOpKind::NotEqualsString => Value::Boolean(left.expect_string() != right.expect_string()),
+            BinOpKind::LessThanString => Value::Boolean(left.expect_string() < right.expect_string()),
+            BinOpKind::LessThanOrEqualString => Value::Boolean(left.expect_string() <= right.expect_string()),
+            BinOpKind::GreaterThanString => Value::Boolean(left.expect_string() > right.expect_string()),
+            BinOpKind::GreaterThanOrEqualString => Value::Boolean(left.expect_string() >= right.expect_string()),
+            BinOpKind::Equals => Value::Boolean(left == right),
+            BinOpKind::NotEquals => Value::Boolean(left != right),
+            BinOpKind::LessThanDecimal => Value::Boolean(left.expect_decimal() < right.expect_decimal()),
+            BinOpKind::LessThanOrEqualDecimal => {
+                Value::Boolean(left.expect_decimal() <= right.expect_decimal())
+            }
+            BinOpKind::GreaterThanDecimal => Value::Boolean(left.expect_decimal() > right.expect_decimal()),
+            BinOpKind::GreaterThanOrEqualDecimal => {
+                Value::Boolean(left.expect_decimal() >= right.expect_decimal())
+            }
+            BinOpKind::LogicalAnd | BinOpKind::LogicalOr => right,
+            _ => panic!("Operator {:?} cannot be applied to {}", operator, operand_type),
+        })
+    }
+
+    /// Calls `builtin`, `span` is where invalid arguments are reported.
+    fn call_builtin(builtin: BuiltinFunction, arguments: &[Value], span: TextSpan) -> Result<Value, Diagnostic> {
+        match builtin {
+            BuiltinFunction::ParseInt => {
+                let string = arguments[0].expect_string();
+                string.parse().map(Value::Number)
+                    .map_err(|_| runtime_error(format!("Cannot parse '{}' as int", string), span))
+            }
+            BuiltinFunction::ParseFloat => {
+                let string = arguments[0].expect_string();
+                string.parse().map(Value::Decimal)
+                    .map_err(|_| runtime_error(format!("Cannot parse '{}' as float", string), span))
+            }
+            BuiltinFunction::Len => Ok(Value::Number(arguments[0].expect_string().chars().count() as i64)),
+        }
+    }
+
+    /// The byte offset of the character at `index`, or of the end of `string` if `index`
+    /// is its length.
+    fn char_boundary(string: &str, index: i128) -> Option<usize> {
+        let index = usize::try_from(index).ok()?;
+        string.char_indices().map(|(offset, _)| offset).chain(std::iter::once(string.len())).nth(index)
+    }
+
     fn expect_last_value(&self) -> Value {
         self.last_value
             .as_ref()
@@ -164,12 +420,131 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
     ) {
     }
 
+    fn visit_statement(&mut self, ast: &mut Ast, statement: StmtId) {
+        // The top-level statements after an error
+        if self.control_flow.is_none() {
+            self.do_visit_statement(ast, statement);
+        }
+    }
+
     fn visit_while_statement(&mut self, ast: &mut Ast, while_statement: &WhileStmt) {
         self.push_frame();
-        self.visit_expression(ast, while_statement.condition);
-        while self.expect_last_value().expect_boolean() {
+        while let Some(condition) = self.evaluate(ast, while_statement.condition) {
+            if !condition.expect_boolean() {
+                break;
+            }
             self.visit_expression(ast, while_statement.body);
-            self.visit_expression(ast, while_statement.condition);
+            match self.take_control_flow_for(&while_statement.label) {
+                Some(ControlFlow::Break { .. }) => break,
+                // Exits an outer loop or the enclosing function, or is an error
+                None if self.control_flow.is_some() => break,
+                _ => {}
+            }
+        }
+        self.pop_frame();
+    }
+
+    fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, _expr: &Expr) {
+        self.push_frame();
+        loop {
+            self.visit_expression(ast, loop_expr.body);
+            match self.take_control_flow_for(&loop_expr.label) {
+                Some(ControlFlow::Break { value, .. }) => {
+                    if value.is_some() {
+                        self.last_value = value;
+                    }
+                    break;
+                }
+                None if self.control_flow.is_some() => break,
+                _ => {}
+            }
+        }
+        self.pop_frame();
+    }
+
+    fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+        let value = match break_statement.value {
+            Some(value) => match self.evaluate(ast, value) {
+                Some(value) => Some(value),
+                None => return,
+            },
+            None => None,
+        };
+        let label = break_statement.label.as_ref().map(|label| label.span.literal.clone());
+        self.control_flow = Some(ControlFlow::Break { label, value });
+    }
+
+    fn visit_return_statement(&mut self, ast: &mut Ast, return_statement: &ReturnStmt) {
+        let value = match return_statement.return_value {
+            Some(value) => match self.evaluate(ast, value) {
+                Some(value) => Some(value),
+                None => return,
+            },
+            None => None,
+        };
+        self.control_flow = Some(ControlFlow::Return { value });
+    }
+
+    fn visit_continue_statement(&mut self, _ast: &mut Ast, continue_statement: &ContinueStmt) {
+        let label = continue_statement.label.as_ref().map(|label| label.span.literal.clone());
+        self.control_flow = Some(ControlFlow::Continue { label });
+    }
+
+    fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, _stmt: &Stmt) {
+        // The bounds and the step are evaluated once, before the first iteration
+        let Some(start) = self.evaluate(ast, for_statement.start) else {
+            return;
+        };
+        let Some(range) = &for_statement.range else {
+            let string = start;
+            self.push_frame();
+            for character in string.expect_string().chars() {
+                self.frames.insert(for_statement.variable_idx, Value::Char(character));
+                self.visit_expression(ast, for_statement.body);
+                match self.take_control_flow_for(&for_statement.label) {
+                    Some(ControlFlow::Break { .. }) => break,
+                    None if self.control_flow.is_some() => break,
+                    _ => {}
+                }
+            }
+            self.pop_frame();
+            return;
+        };
+        // The bounds, the step and the variable have the same integer type
+        let integer = self.global_scope.variables.get(for_statement.variable_idx).ty.integer_type().unwrap();
+        let mut current = start.expect_integer();
+        let Some(end) = self.evaluate(ast, range.end) else {
+            return;
+        };
+        let end = end.expect_integer();
+        let step = match &range.step {
+            Some(step) => match self.evaluate(ast, step.expr) {
+                Some(value) if value.expect_integer() > 0 => value.expect_integer(),
+                Some(value) => {
+                    let span = ast.query_expr(step.expr).span(ast);
+                    self.set_result(Err(runtime_error(format!("For loop step must be positive, found {}", value), span)));
+                    return;
+                }
+                None => return,
+            },
+            None => 1,
+        };
+        self.push_frame();
+        self.frames.insert(for_statement.variable_idx, Self::integer_value(current, integer));
+        while current < end || (for_statement.is_inclusive() && current == end) {
+            self.visit_expression(ast, for_statement.body);
+            match self.take_control_flow_for(&for_statement.label) {
+                Some(ControlFlow::Break { .. }) => break,
+                None if self.control_flow.is_some() => break,
+                _ => {}
+            }
+            // The body may assign the loop variable
+            current = self.frames.get(&for_statement.variable_idx).unwrap().expect_integer() + step;
+            // Ends instead of overflowing, like after the last iteration of `0u8..=255u8`
+            if current > integer.max() {
+                break;
+            }
+            self.frames.update(for_statement.variable_idx, Self::integer_value(current, integer));
         }
         self.pop_frame();
     }
@@ -178,14 +553,19 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
         self.push_frame();
         for statement in &block_statement.stmts {
             self.visit_statement(ast, *statement);
+            if self.control_flow.is_some() {
+                break;
+            }
         }
         self.pop_frame();
     }
 
     fn visit_if_expression(&mut self, ast: &mut Ast, if_statement: &IfExpr, _expr: &Expr) {
+        let Some(condition) = self.evaluate(ast, if_statement.condition) else {
+            return;
+        };
         self.push_frame();
-        self.visit_expression(ast, if_statement.condition);
-        if self.expect_last_value().expect_boolean() {
+        if condition.expect_boolean() {
             self.push_frame();
             self.visit_expression(ast, if_statement.then_branch);
             self.pop_frame();
@@ -198,46 +578,112 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
     }
 
     fn visit_let_statement(&mut self, ast: &mut Ast, let_statement: &LetStmt, _stmt: &Stmt) {
-        self.visit_expression(ast, let_statement.initializer);
-        self.frames
-            .insert(let_statement.variable_idx, self.expect_last_value());
+        let Some(value) = self.evaluate(ast, let_statement.initializer) else {
+            return;
+        };
+        self.frames.insert(let_statement.variable_idx, value);
     }
 
-    // Inside visit_call_expression method
-    fn visit_call_expression(&mut self, ast: &mut Ast, call_expression: &CallExpr, _expr: &Expr) {
+    fn visit_call_expression(&mut self, ast: &mut Ast, call_expression: &CallExpr, expr: &Expr) {
         let function_name = call_expression.function_name();
-        let function = self
-            .global_scope
-            .lookup_function(function_name)
-            .map(|f| self.global_scope.functions.get(f))
-            .expect(
-                format!(
-                    "Function '{}' not found",
-                    call_expression.callee.span.literal
-                )
-                .as_str(),
-            );
-
         let mut arguments = Vec::new();
         for argument in &call_expression.arguments {
-            self.visit_expression(ast, *argument);
-            arguments.push(
-                self.last_value
-                    .as_ref()
-                    .cloned()
-                    .expect("Expected last value to be set"),
-            );
+            let Some(argument) = self.evaluate(ast, *argument) else {
+                return;
+            };
+            arguments.push(argument);
         }
 
+        let Some(function) = self.global_scope.lookup_function(function_name) else {
+            let builtin = BuiltinFunction::lookup(function_name)
+                .unwrap_or_else(|| panic!("Function '{}' not found", function_name));
+            self.set_result(Self::call_builtin(builtin, &arguments, expr.span(ast)));
+            return;
+        };
+        let function = self.global_scope.functions.get(function);
+
         self.push_frame();
         for (argument, param) in arguments.iter().zip(function.parameters.iter()) {
             self.frames.insert(*param, argument.clone());
         }
 
-        self.visit_expression(ast, function.body);
+        self.visit_function_body(ast, function.body);
         self.pop_frame();
     }
 
+    fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, expr: &Expr) {
+        let Some(value) = self.evaluate(ast, cast_expr.expr) else {
+            return;
+        };
+        let integer = expr.ty.integer_type();
+        self.last_value = Some(match (value, &expr.ty) {
+            (value @ (Value::Number(_) | Value::Unsigned(_)), Type::Float) => Value::Decimal(value.expect_integer() as f64),
+            // Truncates toward zero, saturates and maps NaN to 0, like the C runtime
+            (Value::Decimal(value), _) if integer.is_some() => {
+                let integer = integer.unwrap();
+                Self::integer_value((value as i128).clamp(integer.min(), integer.max()), integer)
+            }
+            (value @ (Value::Number(_) | Value::Unsigned(_)), Type::Bool) => Value::Boolean(value.expect_integer() != 0),
+            (Value::Boolean(value), _) if integer.is_some() => Self::integer_value(value as i128, integer.unwrap()),
+            (Value::Char(value), _) if integer.is_some() => {
+                let integer = integer.unwrap();
+                Self::integer_value((value as i128).min(integer.max()), integer)
+            }
+            // Keeps the low bits, like a C cast
+            (value @ (Value::Number(_) | Value::Unsigned(_)), _) if integer.is_some() => {
+                Self::integer_value(integer.unwrap().wrap(value.expect_integer()), integer.unwrap())
+            }
+            (value @ (Value::Number(_) | Value::Unsigned(_)), Type::Char) => Value::Char(
+                u32::try_from(value.expect_integer()).ok().and_then(char::from_u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+            ),
+            (value, Type::String) => Value::String(Arc::new(value.to_string())),
+            (value, _) => value,
+        });
+    }
+
+    fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, expr: &Expr) {
+        let Some(target) = self.evaluate(ast, index_expr.target) else {
+            return;
+        };
+        let string = target.expect_string();
+        let Some(index) = self.evaluate(ast, index_expr.index) else {
+            return;
+        };
+        let index = index.expect_integer();
+        let character = usize::try_from(index).ok().and_then(|index| string.chars().nth(index));
+        self.set_result(character.map(Value::Char).ok_or_else(|| {
+            runtime_error(format!("Index {} is out of bounds for a string of length {}", index, string.chars().count()), expr.span(ast))
+        }));
+    }
+
+    fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, expr: &Expr) {
+        let Some(target) = self.evaluate(ast, slice_expr.target) else {
+            return;
+        };
+        let string = target.expect_string();
+        let Some(start) = self.evaluate(ast, slice_expr.start) else {
+            return;
+        };
+        let start = start.expect_integer();
+        let Some(end) = self.evaluate(ast, slice_expr.end) else {
+            return;
+        };
+        let end = end.expect_integer();
+        // Wide enough for the end after the maximum of every integer type
+        let exclusive_end = end + slice_expr.is_inclusive() as i128;
+        let range = format!("{}{}{}", start, slice_expr.range_operator.span.literal, end);
+        if start > exclusive_end {
+            self.set_result(Err(runtime_error(format!("Slice {} starts after its end", range), expr.span(ast))));
+            return;
+        }
+        let bounds = Self::char_boundary(string, start).zip(Self::char_boundary(string, exclusive_end));
+        let Some((start, end)) = bounds else {
+            self.set_result(Err(runtime_error(format!("Slice {} is out of bounds for a string of length {}", range, string.chars().count()), expr.span(ast))));
+            return;
+        };
+        self.last_value = Some(Value::String(Arc::new(string[start..end].to_string())));
+    }
+
     fn visit_variable_expression(&mut self, _ast: &mut Ast, var_expr: &VarExpr, _expr: &Expr) {
         let identifier = &var_expr.identifier.span.literal;
         self.last_value = Some(
@@ -255,8 +701,31 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
         );
     }
 
-    fn visit_number_expression(&mut self, _ast: &mut Ast, number: &NumberExpr, _expr: &Expr) {
-        self.last_value = Some(Value::Number(number.number));
+    fn visit_assignment_expression(&mut self, ast: &mut Ast, assignment_expression: &AssignExpr, expr: &Expr) {
+        let Some(mut value) = self.evaluate(ast, assignment_expression.expression) else {
+            return;
+        };
+        if let Some(operator) = &assignment_expression.operator {
+            let current = self.frames
+                .get(&assignment_expression.variable_idx)
+                .expect("Assigned variable is not initialized")
+                .clone();
+            let ty = &self.global_scope.variables.get(assignment_expression.variable_idx).ty;
+            match Self::apply_binary_operator(&operator.kind, ty, current, value, &expr.span(ast)) {
+                Ok(result) => value = result,
+                Err(error) => {
+                    self.set_result(Err(error));
+                    return;
+                }
+            }
+        }
+        self.frames.update(assignment_expression.variable_idx, value.clone());
+        self.last_value = Some(value);
+    }
+
+    fn visit_number_expression(&mut self, _ast: &mut Ast, number: &NumberExpr, expr: &Expr) {
+        let integer = expr.ty.integer_type().expect("Literals have an integer type");
+        self.last_value = Some(Self::integer_value(number.number as i128, integer));
     }
 
     fn visit_string_expression(
@@ -268,6 +737,31 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
         self.last_value = Some(Value::String(Arc::new(string.string.clone())));
     }
 
+    fn visit_interpolated_string_expression(
+        &mut self,
+        ast: &mut Ast,
+        interpolated_string: &InterpolatedStringExpr,
+        _expr: &Expr,
+    ) {
+        let mut string = String::new();
+        for part in &interpolated_string.parts {
+            match part {
+                InterpolatedStringPart::Literal(value) => string.push_str(value),
+                InterpolatedStringPart::Expr(expr) => {
+                    let Some(value) = self.evaluate(ast, *expr) else {
+                        return;
+                    };
+                    string.push_str(&value.to_string());
+                }
+            }
+        }
+        self.last_value = Some(Value::String(Arc::new(string)));
+    }
+
+    fn visit_char_expression(&mut self, _ast: &mut Ast, character: &CharExpr, _expr: &Expr) {
+        self.last_value = Some(Value::Char(character.value));
+    }
+
     fn visit_decimal_expression(
         &mut self,
         _ast: &mut Ast,
@@ -289,127 +783,535 @@ impl<'a> ASTVisitor for ASTEvaluator<'a> {
         &mut self,
         ast: &mut Ast,
         unary_expression: &UnaryExpr,
+        expr: &Expr,
+    ) {
+        let integer = expr.ty.integer_type();
+        if let (UnOpKind::Minus, ExprKind::Number(number)) = (&unary_expression.operator.kind, &ast.query_expr(unary_expression.operand).kind) {
+            // The literal itself may not fit, like the 128 of `-128i8`
+            self.last_value = Some(Self::integer_value(-(number.number as i128), integer.unwrap()));
+            return;
+        }
+        let Some(operand) = self.evaluate(ast, unary_expression.operand) else {
+            return;
+        };
+        self.set_result(match unary_expression.operator.kind {
+            UnOpKind::Minus => Self::checked_integer_value(-operand.expect_integer(), integer.unwrap(), &expr.span(ast)),
+            UnOpKind::MinusDecimal => Ok(Value::Decimal(-operand.expect_decimal())),
+            UnOpKind::BitwiseNot => Ok(Self::integer_value(integer.unwrap().wrap(!operand.expect_integer()), integer.unwrap())),
+            UnOpKind::LogicalNot => Ok(Value::Boolean(!operand.expect_boolean())),
+        });
+    }
+
+    fn visit_binary_expression(&mut self, ast: &mut Ast, binary_expr: &BinaryExpr, expr: &Expr) {
+        let Some(left) = self.evaluate(ast, binary_expr.left) else {
+            return;
+        };
+        // The right operand of `&&` and `||` is only evaluated if it can change the result
+        match binary_expr.operator.kind {
+            BinOpKind::LogicalAnd if !left.expect_boolean() => return,
+            BinOpKind::LogicalOr if left.expect_boolean() => return,
+            _ => {}
+        }
+        let Some(right) = self.evaluate(ast, binary_expr.right) else {
+            return;
+        };
+
+        let operand_type = &ast.query_expr(binary_expr.left).ty;
+        self.set_result(Self::apply_binary_operator(&binary_expr.operator.kind, operand_type, left, right, &expr.span(ast)));
+    }
+
+    
+
+    fn visit_parenthesized_expression(
+        &mut self,
+        ast: &mut Ast,
+        parenthesized_expression: &ParenthesizedExpr,
         _expr: &Expr,
     ) {
-        self.visit_expression(ast, unary_expression.operand);
-        let operand = self.expect_last_value().expect_number();
-        self.last_value = Some(Value::Number(match unary_expression.operator.kind {
-            UnOpKind::Minus => -operand,
-            UnOpKind::BitwiseNot => !operand,
-        }));
+        self.visit_expression(ast, parenthesized_expression.expression);
     }
+}
 
-    fn visit_binary_expression(&mut self, ast: &mut Ast, binary_expr: &BinaryExpr, _expr: &Expr) {
-        self.visit_expression(ast, binary_expr.left);
-        let left = self.expect_last_value();
-        self.visit_expression(ast, binary_expr.right);
-        let right = self.expect_last_value();
-       
-        self.last_value = Some(match binary_expr.operator.kind {
-            BinOpKind::Plus => match (left.clone(), right.clone()) {
-                (Value::Number(left_val), Value::Number(right_val)) => {
-                    Value::Number(left_val + right_val)
-                }
-                (Value::Decimal(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val + right_val)
-                }
-                (Value::Number(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val as f64 + right_val)
-                }
-                (Value::Decimal(left_val), Value::Number(right_val)) => {
-                    Value::Decimal(left_val + right_val as f64)
-                }
-                (Value::String(left_val), Value::String(right_val)) => {
-                    let mut string = String::with_capacity(left_val.len() + right_val.len());
-                    print!("left_val: {:?}", left_val);
-                    string.push_str(&left_val as &str);
-                    string.push_str(&right_val as &str);
-                    Value::String(Arc::new(string))
-                }
-                _ => panic!("Invalid operands for addition"),
-            },
-            BinOpKind::Minus => match (left.clone(), right.clone()) {
-                (Value::Number(left_val), Value::Number(right_val)) => {
-                    Value::Number(left_val - right_val)
-                }
-                (Value::Decimal(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val - right_val)
+#[cfg(test)]
+mod test {
+    use crate::ast::evaluator::Value;
+    use crate::compilation_unit::CompilationUnit;
+
+    fn assert_evaluates_to(input: &str, expected: Value) {
+        let mut compilation_unit = CompilationUnit::compile(input).expect("Failed to compile");
+        assert_eq!(compilation_unit.evaluate().expect("Failed to evaluate"), Some(expected));
+    }
+
+    fn assert_runtime_error(input: &str, span: &str, message: &str) {
+        let mut compilation_unit = CompilationUnit::compile(input).expect("Failed to compile");
+        let error = compilation_unit.evaluate().expect_err("Expected a runtime error");
+        assert_eq!(error.message, message);
+        assert_eq!(error.span.literal, span);
+    }
+
+    #[test]
+    pub fn should_return_early_from_nested_blocks() {
+        let input = "\
+        func sign(a: int) -> int {
+            if a < 0 {
+                {
+                    return -1
                 }
-                (Value::Number(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val as f64 - right_val)
+            }
+            if a == 0 {
+                return 0
+            }
+            1
+        }
+        func main() -> int {
+            sign(-5) * 100 + sign(0) * 10 + sign(7)
+        }
+        ";
+
+        assert_evaluates_to(input, Value::Number(-99));
+    }
+
+    #[test]
+    pub fn should_return_from_inside_loops() {
+        let input = "\
+        func first_multiple(of: int, above: int) -> int {
+            let i = above
+            while true {
+                for j in 0..of {
+                    if (i + j) % of == 0 {
+                        return i + j
+                    }
                 }
-                (Value::Decimal(left_val), Value::Number(right_val)) => {
-                    Value::Decimal(left_val - right_val as f64)
+                i += of
+            }
+            return -1
+        }
+        func main() -> int {
+            first_multiple(7, 30)
+        }
+        ";
+
+        assert_evaluates_to(input, Value::Number(35));
+    }
+
+    #[test]
+    pub fn should_break_and_continue_labeled_loops() {
+        let input = "\
+        let pairs = \"\"
+        'rows: for i in 0..4 {
+            'columns: for j in 0..4 {
+                if j > i {
+                    continue 'rows
                 }
-                _ => panic!("Invalid operands for subtraction"),
-            },
-            BinOpKind::Multiply => match (left.clone(), right.clone()) {
-                (Value::Number(left_val), Value::Number(right_val)) => {
-                    Value::Number(left_val * right_val)
+                if i == 3 {
+                    break 'rows
                 }
-                (Value::Decimal(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val * right_val)
+                pairs = pairs + \"${i}${j} \"
+                let k = 0
+                while true {
+                    k += 1
+                    if k == 2 {
+                        continue 'columns
+                    }
                 }
-                (Value::Number(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val as f64 * right_val)
+            }
+        }
+        let visited = 0
+        'outer: while visited < 100 {
+            loop {
+                visited += 1
+                if visited % 10 == 0 {
+                    continue 'outer
                 }
-                (Value::Decimal(left_val), Value::Number(right_val)) => {
-                    Value::Decimal(left_val * right_val as f64)
+                if visited == 25 {
+                    break 'outer
                 }
+            }
+        }
+        let skipped = 0
+        for i in 0..10 {
+            if i % 2 == 0 {
+                continue
+            }
+            skipped += i
+        }
+        \"${pairs}${visited} ${skipped}\"
+        ";
 
-                _ => panic!("Invalid operands for Multiplication"),
-            },
-            BinOpKind::Divide => match (left.clone(), right.clone()) {
-                (Value::Number(left_val), Value::Number(right_val)) => {
-                    Value::Number(left_val / right_val)
-                }
-                (Value::Decimal(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val / right_val)
-                }
-                (Value::Number(left_val), Value::Decimal(right_val)) => {
-                    Value::Decimal(left_val as f64 / right_val)
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("00 10 11 20 21 22 25 25".to_string())));
+    }
+
+    #[test]
+    pub fn should_exit_loops_with_break_values() {
+        let input = "\
+        let i = 0
+        let first_square = loop {
+            i += 1
+            if i * i > 50 {
+                break i * i
+            }
+        }
+        let found = 'search: loop {
+            for j in 1..10 {
+                if j * 7 % 5 == 3 {
+                    break 'search j
                 }
-                (Value::Decimal(left_val), Value::Number(right_val)) => {
-                    Value::Decimal(left_val / right_val as f64)
-                },
-                _ => panic!("Invalid operands for Division"),
             }
+            break 'search 0
+        }
+        let nested = loop {
+            let inner = loop {
+                break 4
+            }
+            break inner * 10
+        }
+        first_square + found * 100 + nested * 1000
+        ";
+
+        assert_evaluates_to(input, Value::Number(64 + 400 + 40000));
+    }
 
-            BinOpKind::Power => {
-                Value::Number(left.expect_number().pow(right.expect_number() as u32))
+    #[test]
+    pub fn should_evaluate_recursive_functions() {
+        let input = "\
+        func fib(n: int) -> int {
+            if n < 2 {
+                return n
             }
+            return fib(n - 1) + fib(n - 2)
+        }
+        func factorial(n: int) -> int {
+            if n == 0 {
+                return 1
+            }
+            n * factorial(n - 1)
+        }
+        func main() -> int {
+            fib(15) + factorial(10)
+        }
+        ";
 
-            BinOpKind::BitwiseAnd => Value::Number(left.expect_number() & right.expect_number()),
-            BinOpKind::BitwiseOr => Value::Number(left.expect_number() | right.expect_number()),
-            BinOpKind::BitwiseXor => Value::Number(left.expect_number() ^ right.expect_number()),
-            BinOpKind::Equals => Value::Boolean(left == right),
-            BinOpKind::NotEquals => Value::Boolean(left != right),
-            BinOpKind::LessThan => Value::Boolean(left.expect_number() < right.expect_number()),
-            BinOpKind::LessThanOrEqual => {
-                Value::Boolean(left.expect_number() <= right.expect_number())
-            }
-            BinOpKind::GreaterThan => Value::Boolean(left.expect_number() > right.expect_number()),
-            BinOpKind::GreaterThanOrEqual => {
-                Value::Boolean(left.expect_number() >= right.expect_number())
-            }
-            BinOpKind::MinusDecimal => todo!(),
-            BinOpKind::MultiplyDecimal => todo!(),
-            BinOpKind::DivideDecimal => todo!(),
-            BinOpKind::PlusDecimal => todo!(),
-            BinOpKind::PlusString => todo!(),
-        });
-    
-        
+        assert_evaluates_to(input, Value::Number(610 + 3628800));
     }
 
-    
+    #[test]
+    pub fn should_not_return_from_the_caller() {
+        let input = "\
+        func one() -> int {
+            return 1
+        }
+        func main() -> int {
+            let a = one()
+            a + 1
+        }
+        ";
 
-    fn visit_parenthesized_expression(
-        &mut self,
-        ast: &mut Ast,
-        parenthesized_expression: &ParenthesizedExpr,
-        _expr: &Expr,
-    ) {
-        self.visit_expression(ast, parenthesized_expression.expression);
+        assert_evaluates_to(input, Value::Number(2));
+    }
+
+    #[test]
+    pub fn should_call_functions_declared_after_main() {
+        let input = "\
+        func main() -> int {
+            if is_even(10) && is_odd(7) {
+                return offset + 1
+            }
+            0
+        }
+        func is_even(n: int) -> bool {
+            if n == 0 {
+                return true
+            }
+            is_odd(n - 1)
+        }
+        func is_odd(n: int) -> bool {
+            if n == 0 {
+                return false
+            }
+            is_even(n - 1)
+        }
+        let offset = 41
+        ";
+
+        assert_evaluates_to(input, Value::Number(42));
+    }
+
+    #[test]
+    pub fn should_step_through_exclusive_and_inclusive_ranges() {
+        let input = "\
+        let exclusive = \"\"
+        for i in 0..9 step 3 {
+            exclusive = exclusive + \"${i}\"
+        }
+        let inclusive = \"\"
+        for i in 0..=9 step 3 {
+            inclusive = inclusive + \"${i}\"
+        }
+        let uneven = \"\"
+        for i in 0..=8 step 3 {
+            uneven = uneven + \"${i}\"
+        }
+        \"${exclusive} ${inclusive} ${uneven}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("036 0369 036".to_string())));
+    }
+
+    #[test]
+    pub fn should_report_non_positive_steps_at_the_step() {
+        assert_runtime_error("let s = 0\nfor i in 0..10 step s {}", "s", "For loop step must be positive, found 0");
+        assert_runtime_error("for i in 0..10 step 2 - 3 {}", "2 - 3", "For loop step must be positive, found -1");
+    }
+
+    #[test]
+    pub fn should_keep_parameters_apart_from_globals_with_the_same_name() {
+        let input = "\
+        func scale(x: int) -> int {
+            x * factor
+        }
+        let x = 1
+        let factor = 10
+        scale(5) + x
+        ";
+
+        assert_evaluates_to(input, Value::Number(51));
+    }
+
+    #[test]
+    pub fn should_evaluate_float_arithmetic_and_comparisons() {
+        let input = "\
+        let a = 56.2
+        let b = -a / 4.0 + 0.05
+        b *= 2.0
+        if b <= -28.0 && b > -28.1 {
+            b
+        } else {
+            0.0
+        }
+        ";
+
+        assert_evaluates_to(input, Value::Decimal((-56.2 / 4.0 + 0.05) * 2.0));
+    }
+
+    #[test]
+    pub fn should_not_evaluate_the_right_operand_when_the_left_decides() {
+        let input = "\
+        let zero = 0
+        let calls = 0
+        func touch() -> bool {
+            calls += 1
+            true
+        }
+        let a = false && 1 / zero == 0
+        let b = true || parse_int(\"x\") == 0
+        let c = false && touch()
+        let d = true || touch()
+        let e = true && touch()
+        \"${a} ${b} ${c} ${d} ${e} ${calls}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("false true false true true 1".to_string())));
+    }
+
+    #[test]
+    pub fn should_read_the_target_of_compound_assignments_once_after_the_value() {
+        let input = "\
+        let x = 1
+        let calls = 0
+        func f() -> int {
+            calls += 1
+            x = 10
+            1
+        }
+        x += f()
+        let y = 3
+        y ^= (y = 6)
+        \"${x} ${calls} ${y}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("11 1 0".to_string())));
+    }
+
+    #[test]
+    pub fn should_convert_between_types_with_casts() {
+        let input = "\
+        let truncated = -2.7 as int
+        let nan = (0.0 / 0.0) as int
+        let saturated = 1e300 as int == 9223372036854775807
+        let parsed = parse_int(\"-40\") + (true as int) + (parse_float(\"1.5\") * 2.0) as int
+        \"${truncated} ${nan} ${saturated} ${parsed} \" + (0.25 as string) + \" \" + (7 as float as string)
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("-2 0 true -36 0.25 7".to_string())));
+    }
+
+    #[test]
+    pub fn should_cast_floats_and_chars_to_sized_integers_and_back() {
+        let input = "\
+        let a = 300.7 as u8
+        let b = -5.9 as u8
+        let c = -200.5 as i8
+        let d = (0.0 / 0.0) as u32
+        let e = 'é' as u8
+        let f = '€' as i8
+        let g = 'A' as u64
+        let h = 233u8 as char
+        let i = 18446744073709551615u64 as char
+        \"${a} ${b} ${c} ${d} ${e} ${f} ${g} ${h} ${i}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("255 0 -128 0 233 127 65 é \u{FFFD}".to_string())));
+    }
+
+    #[test]
+    pub fn should_index_and_slice_strings_by_character() {
+        let input = "\
+        let word = \"héllo wörld\"
+        let n = len(word)
+        let ordered = \"abc\" < \"abd\" && \"é\" > \"z\" && word == \"héllo \" + \"wörld\"
+        \"${n} ${word[1]} ${word[1..=4]} ${word[6..n]} ${ordered} ${word[0..0]}|\" + word[n - 1] as string
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("11 é éllo wörld true |d".to_string())));
+    }
+
+    #[test]
+    pub fn should_index_slice_and_bound_ranges_with_any_integer_type() {
+        let input = "\
+        let word = \"héllo\"
+        let i = 1usize
+        let n = 0
+        for j in 250u8..=255u8 {
+            n += 1
+        }
+        let m = 0
+        for k in 0u8..7u8 step 2u8 {
+            m += 1
+        }
+        \"${word[i]} ${word[0usize..=2usize]} ${word[1u8..3u8]} ${n} ${m}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("é hél él 6 4".to_string())));
+    }
+
+    #[test]
+    pub fn should_evaluate_chars() {
+        let input = "\
+        let vowels = 0
+        for c in \"héllo\" {
+            if c == 'a' || c == 'e' || c == 'o' || c == 'é' {
+                vowels += 1
+            }
+        }
+        let ordered = 'a' < 'b' && 'é' > 'z' && \"abc\"[2] == 'c'
+        \"${vowels} ${'a' as int} ${233 as char} ${-1 as char} ${ordered} \" + '\\n' as string
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new("2 97 é \u{FFFD} true \n".to_string())));
+    }
+
+    #[test]
+    pub fn should_truncate_remainders_and_shift_by_amounts_modulo_the_width() {
+        let input = "\
+        let a = -7 % 3
+        let b = 7 % -3
+        let c = -7 % -3
+        let d = -9223372036854775808 % -1
+        let e = 1 << 64
+        let f = 1 << 65
+        let g = -8 >> 66
+        let h = 1 << -1
+        let i = -1 >>> 127
+        let j = 1u8 << 9
+        let k = 128u8 >> 15i64
+        let l = -128i8 >> 8
+        \"${a} ${b} ${c} ${d} ${e} ${f} ${g} ${h} ${i} ${j} ${k} ${l}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new(
+            "-1 1 -1 0 1 2 -2 -9223372036854775808 1 2 1 -128".to_string()
+        )));
+    }
+
+    #[test]
+    pub fn should_wrap_unsigned_and_truncate_casts_like_stdint_types() {
+        let input = "\
+        let a = 200u8 + 100u8
+        let b = 100u8 - 200u8
+        let c = 65535u16 * 65535u16
+        let d = 18446744073709551615u64 + 1u64
+        let e = ~0u8
+        let f = -1i8 >>> 1
+        let g = 1i8 << 7
+        let h = 300 as u8
+        let i = -1 as u32
+        let j = 255u8 as i8
+        let k = (-2i8) ** 7i8
+        let l = -9223372036854775808
+        \"${a} ${b} ${c} ${d} ${e} ${f} ${g} ${h} ${i} ${j} ${k} ${l}\"
+        ";
+
+        assert_evaluates_to(input, Value::String(std::sync::Arc::new(
+            "44 156 1 0 255 127 -128 44 4294967295 -1 -128 -9223372036854775808".to_string()
+        )));
+    }
+
+    #[test]
+    pub fn should_report_signed_overflow_at_runtime() {
+        let cases = [
+            ("let a = 100i8\na + a", "a + a", "Integer overflow, the result does not fit into 'i8'"),
+            ("let a = 9223372036854775807\na * 2", "a * 2", "Integer overflow, the result does not fit into 'int'"),
+            ("let a = -128i8\nlet b = -a", "-a", "Integer overflow, the result does not fit into 'i8'"),
+            ("let a = 120i8\na += 10i8", "a += 10i8", "Integer overflow, the result does not fit into 'i8'"),
+            ("let a = 0u32\n1u32 / a", "1u32 / a", "Division by zero"),
+            ("let a = 2\na ** -1", "a ** -1", "Cannot raise an integer to the negative power -1"),
+        ];
+        for (input, span, message) in cases {
+            assert_runtime_error(input, span, message);
+        }
+    }
+
+    #[test]
+    pub fn should_stop_at_runtime_errors_in_nested_calls_and_loops() {
+        let input = "\
+        func divide(a: int, b: int) -> int {
+            let results = 0
+            for i in 0..3 {
+                results += a / (b - i)
+            }
+            results
+        }
+        let total = 0
+        while true {
+            total += divide(10, 1)
+        }
+        total
+        ";
+
+        assert_runtime_error(input, "a / (b - i)", "Division by zero");
+    }
+
+    #[test]
+    pub fn should_report_unparseable_strings_at_runtime() {
+        let cases = [
+            ("let x = parse_int(\"abc\")", "parse_int(\"abc\")", "Cannot parse 'abc' as int"),
+            ("let x = parse_int(\"99999999999999999999\")", "parse_int(\"99999999999999999999\")", "Cannot parse '99999999999999999999' as int"),
+            ("let s = \"1.5.2\"\nlet x = parse_float(s)", "parse_float(s)", "Cannot parse '1.5.2' as float"),
+        ];
+        for (input, span, message) in cases {
+            assert_runtime_error(input, span, message);
+        }
+    }
+
+    #[test]
+    pub fn should_report_out_of_bounds_string_access_at_runtime() {
+        let cases = [
+            ("let s = \"añb\"\n(s[0] as int) + (s[3] as int)", "s[3]", "Index 3 is out of bounds for a string of length 3"),
+            ("let s = \"añb\"\ns[2..1]", "s[2..1]", "Slice 2..1 starts after its end"),
+            ("let s = \"añb\"\ns[1..=3]", "s[1..=3]", "Slice 1..=3 is out of bounds for a string of length 3"),
+        ];
+        for (input, span, message) in cases {
+            assert_runtime_error(input, span, message);
+        }
     }
 }
@@ -1,22 +1,31 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use unicode_security::MixedScript;
+use unicode_xid::UnicodeXID;
+use crate::diagnostics::DiagnosticsBagCell;
 use crate::text::span::TextSpan;
-use std::process;
+use crate::typings::{IntegerType, Type};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind {
     // Literals
-    Number(i64),
+    /// An integer literal and its type suffix, like `255u8`
+    Number(u64, Option<IntegerType>),
     Decimal(f64),
-    String(String),
+    Char(char),
+    String { fragments: Vec<StringFragment>, kind: StringKind },
     StringEnd,
     // Operators
     Plus,
     Minus,
     Asterisk,
     Slash,
+    Percent,
     Equals,
     Ampersand,
     Dot,
+    DotDot,
+    DotDotEquals,
     Pipe,
     Caret,
     DoubleAsterisk,
@@ -25,8 +34,27 @@ pub enum TokenKind {
     LessThan,
     GreaterThanEquals,
     LessThanEquals,
+    LessThanLessThan,
+    GreaterThanGreaterThan,
+    GreaterThanGreaterThanGreaterThan,
     EqualsEquals,
     BangEquals,
+    // Compound assignment
+    PlusEquals,
+    MinusEquals,
+    AsteriskEquals,
+    SlashEquals,
+    PercentEquals,
+    DoubleAsteriskEquals,
+    AmpersandEquals,
+    PipeEquals,
+    CaretEquals,
+    LessThanLessThanEquals,
+    GreaterThanGreaterThanEquals,
+    GreaterThanGreaterThanGreaterThanEquals,
+    Bang,
+    AmpersandAmpersand,
+    PipePipe,
     // Keywords
     Let,
     If,
@@ -34,11 +62,19 @@ pub enum TokenKind {
     True,
     False,
     While,
+    For,
+    In,
+    Loop,
+    As,
+    Break,
+    Continue,
     Func,
     Return,
     // Separators
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
     OpenBrace,
     CloseBrace,
     Comma,
@@ -48,7 +84,11 @@ pub enum TokenKind {
     // Other
     Bad,
     Whitespace,
+    LineComment,
+    BlockComment,
     Identifier,
+    // A loop label such as `'outer`, the quote is part of the literal
+    Label,
     Eof,
     // Null
   
@@ -56,10 +96,18 @@ pub enum TokenKind {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum StringFragment {
-    Literal { len: usize },
+    Literal { value: String },
+    // The tokens of a `${...}` block, terminated by a `StringEnd` token at the closing brace
     Interpolation { tokens: Vec<Token> },
 }
 
+/// A character of a `''` string before its indentation is stripped.
+enum IndentedChar {
+    Raw(char),
+    Escaped(char),
+    Interpolation(Vec<Token>),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum StringKind {
     // Delimited by one double-quote: "
@@ -69,25 +117,68 @@ pub enum StringKind {
 }
 
 
+/// Every keyword and the token it lexes to. Tools that need to know which words are
+/// reserved (formatter, highlighter) should query this table instead of keeping a copy.
+pub const KEYWORDS: &[(&str, TokenKind)] = &[
+    ("let", TokenKind::Let),
+    ("if", TokenKind::If),
+    ("else", TokenKind::Else),
+    ("true", TokenKind::True),
+    ("false", TokenKind::False),
+    ("while", TokenKind::While),
+    ("for", TokenKind::For),
+    ("in", TokenKind::In),
+    ("loop", TokenKind::Loop),
+    ("as", TokenKind::As),
+    ("break", TokenKind::Break),
+    ("continue", TokenKind::Continue),
+    ("func", TokenKind::Func),
+    ("return", TokenKind::Return),
+];
+
+impl TokenKind {
+    /// The keyword token for `word`, or `None` if it is an ordinary identifier.
+    pub fn keyword(word: &str) -> Option<TokenKind> {
+        KEYWORDS.iter().find(|(keyword, _)| *keyword == word).map(|(_, kind)| kind.clone())
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        KEYWORDS.iter().any(|(_, kind)| kind == self)
+    }
+
+    /// Trivia carries no meaning for the parser and is dropped before parsing.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment)
+    }
+}
+
 impl Display for TokenKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            TokenKind::Number(_) => write!(f, "Number"),
+            TokenKind::Number(..) => write!(f, "Number"),
             TokenKind::Decimal(_) => write!(f, "Decimal"),
             TokenKind::Plus => write!(f, "+"),
             TokenKind::Minus => write!(f, "-"),
             TokenKind::Asterisk => write!(f, "*"),
             TokenKind::Slash => write!(f, "/"),
+            TokenKind::Percent => write!(f, "%"),
             TokenKind::LeftParen => write!(f, "("),
             TokenKind::RightParen => write!(f, ")"),
+            TokenKind::LeftBracket => write!(f, "["),
+            TokenKind::RightBracket => write!(f, "]"),
             TokenKind::Bad => write!(f, "Bad"),
             TokenKind::Whitespace => write!(f, "Whitespace"),
+            TokenKind::LineComment => write!(f, "LineComment"),
+            TokenKind::BlockComment => write!(f, "BlockComment"),
             TokenKind::Eof => write!(f, "Eof"),
             TokenKind::Let => write!(f, "Let"),
             TokenKind::Identifier => write!(f, "Identifier"),
             TokenKind::Equals => write!(f, "="),
             TokenKind::Ampersand => write!(f, "&"),
             TokenKind::Pipe => write!(f, "|"),
+            TokenKind::Bang => write!(f, "!"),
+            TokenKind::AmpersandAmpersand => write!(f, "&&"),
+            TokenKind::PipePipe => write!(f, "||"),
             TokenKind::Caret => write!(f, "^"),
             TokenKind::DoubleAsterisk => write!(f, "**"),
             TokenKind::Tilde => write!(f, "~"),
@@ -97,13 +188,35 @@ impl Display for TokenKind {
             TokenKind::LessThan => write!(f, "<"),
             TokenKind::GreaterThanEquals => write!(f, ">="),
             TokenKind::LessThanEquals => write!(f, "<="),
+            TokenKind::LessThanLessThan => write!(f, "<<"),
+            TokenKind::GreaterThanGreaterThan => write!(f, ">>"),
+            TokenKind::GreaterThanGreaterThanGreaterThan => write!(f, ">>>"),
             TokenKind::EqualsEquals => write!(f, "=="),
             TokenKind::BangEquals => write!(f, "!="),
+            TokenKind::PlusEquals => write!(f, "+="),
+            TokenKind::MinusEquals => write!(f, "-="),
+            TokenKind::AsteriskEquals => write!(f, "*="),
+            TokenKind::SlashEquals => write!(f, "/="),
+            TokenKind::PercentEquals => write!(f, "%="),
+            TokenKind::DoubleAsteriskEquals => write!(f, "**="),
+            TokenKind::AmpersandEquals => write!(f, "&="),
+            TokenKind::PipeEquals => write!(f, "|="),
+            TokenKind::CaretEquals => write!(f, "^="),
+            TokenKind::LessThanLessThanEquals => write!(f, "<<="),
+            TokenKind::GreaterThanGreaterThanEquals => write!(f, ">>="),
+            TokenKind::GreaterThanGreaterThanGreaterThanEquals => write!(f, ">>>="),
             TokenKind::OpenBrace => write!(f, "{{"),
             TokenKind::CloseBrace => write!(f, "}}"),
             TokenKind::True => write!(f, "True"),
             TokenKind::False => write!(f, "False"),
             TokenKind::While => write!(f, "While"),
+            TokenKind::For => write!(f, "For"),
+            TokenKind::In => write!(f, "In"),
+            TokenKind::Loop => write!(f, "Loop"),
+            TokenKind::As => write!(f, "As"),
+            TokenKind::Break => write!(f, "Break"),
+            TokenKind::Continue => write!(f, "Continue"),
+            TokenKind::Label => write!(f, "Label"),
             TokenKind::Func => write!(f, "Func"),
             TokenKind::Return => write!(f, "Return"),
             TokenKind::Comma => write!(f, "Comma"),
@@ -111,6 +224,9 @@ impl Display for TokenKind {
             TokenKind::Arrow => write!(f, "Arrow"),
             TokenKind::SemiColon => write!(f, ";"),
             TokenKind::Dot => write!(f, "."),
+            TokenKind::DotDot => write!(f, ".."),
+            TokenKind::DotDotEquals => write!(f, "..="),
+            TokenKind::Char(_) => write!(f, "Char"),
             TokenKind::String { .. } => write!(f, "String"),
             TokenKind::StringEnd => write!(f, "StringEnd"),
 
@@ -122,68 +238,148 @@ impl Display for TokenKind {
 pub struct Token {
     pub(crate) kind: TokenKind,
     pub(crate) span: TextSpan,
+    // Only filled in by `Lexer::lex_lossless`
+    pub(crate) leading_trivia: Vec<Token>,
+    pub(crate) trailing_trivia: Vec<Token>,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, span: TextSpan) -> Self {
-        Self { kind, span }
+        Self { kind, span, leading_trivia: Vec::new(), trailing_trivia: Vec::new() }
+    }
+
+    /// A zero-width token standing in for one the parser expected but didn't find.
+    pub fn missing(kind: TokenKind, position: usize) -> Self {
+        Self::new(kind, TextSpan::new(position, position, String::new()))
+    }
+
+    pub fn is_missing(&self) -> bool {
+        self.span.literal.is_empty() && !matches!(self.kind, TokenKind::Eof | TokenKind::StringEnd)
+    }
+
+    /// The source text of the token including its trivia.
+    pub fn full_text(&self) -> String {
+        let mut text = String::new();
+        for trivia in &self.leading_trivia {
+            text.push_str(&trivia.span.literal);
+        }
+        text.push_str(&self.span.literal);
+        for trivia in &self.trailing_trivia {
+            text.push_str(&trivia.span.literal);
+        }
+        text
     }
 }
 
 pub struct Lexer<'a> {
     input: &'a str,
+    // Byte offset into `input`, always on a char boundary
     current_pos: usize,
+    diagnostics_bag: DiagnosticsBagCell,
+    // Identifiers that were already linted
+    linted_identifiers: HashSet<String>,
+    // The first identifier seen for each confusable skeleton
+    identifier_skeletons: HashMap<String, String>,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &'a str) -> Self {
-        Self { input, current_pos: 0 }
+    pub fn new(input: &'a str, diagnostics_bag: DiagnosticsBagCell) -> Self {
+        Self {
+            input,
+            current_pos: 0,
+            diagnostics_bag,
+            linted_identifiers: HashSet::new(),
+            identifier_skeletons: HashMap::new(),
+        }
+    }
+
+    /// Lexes the whole input, attaching trivia to the surrounding tokens instead of
+    /// returning it as tokens of its own. Trivia up to and including the next newline
+    /// trails the previous token, everything else leads the next one, so the
+    /// `full_text` of all tokens concatenated is exactly the input.
+    pub fn lex_lossless(mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut leading_trivia = Vec::new();
+        while let Some(mut token) = self.next_token() {
+            if token.kind.is_trivia() {
+                leading_trivia.push(token);
+                continue;
+            }
+            token.leading_trivia = std::mem::take(&mut leading_trivia);
+            token.trailing_trivia = self.consume_trailing_trivia();
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    fn consume_trailing_trivia(&mut self) -> Vec<Token> {
+        let mut trivia = Vec::new();
+        while self.is_trivia_start() {
+            let token = self.next_token().unwrap();
+            let is_newline = token.span.literal == "\n";
+            trivia.push(token);
+            if is_newline {
+                break;
+            }
+        }
+        trivia
+    }
+
+    fn is_trivia_start(&self) -> bool {
+        match self.current_char() {
+            Some('/') => matches!(self.peek_char(1), Some('/' | '*')),
+            Some(c) => Self::is_whitespace(&c),
+            None => false,
+        }
     }
 
     pub fn next_token(&mut self) -> Option<Token> {
         if self.current_pos == self.input.len() {
-            let eof_char: char = '\0';
             self.current_pos += 1;
             return Some(Token::new(
                 TokenKind::Eof,
-                TextSpan::new(0, 0, eof_char.to_string()),
+                TextSpan::new(self.input.len(), self.input.len(), String::new()),
             ));
         }
         let c = self.current_char();
         return c.map(|c| {
             let start = self.current_pos;
             let mut kind = TokenKind::Bad;
-            if Self::is_number_start(&c) {
-                let number: i64 = self.consume_number();
-                kind = TokenKind::Number(number);
-            } 
-            else if Self::is_decimal(&c){
+            if self.is_decimal_start() {
                 let decimal: f64 = self.consume_decimal();
-                kind = TokenKind::Decimal(decimal); 
+                kind = TokenKind::Decimal(decimal);
+            }
+            else if Self::is_number_start(&c) {
+                let (number, suffix) = self.consume_number();
+                kind = TokenKind::Number(number, suffix);
             }
             else if Self::is_whitespace(&c){
                 self.consume();
                 kind = TokenKind::Whitespace;
             }
+            else if c == '\'' && self.peek_char(1) == Some('\'') {
+                let fragments = self.consume_indented_string();
+                kind = TokenKind::String { fragments, kind: StringKind::Indented };
+            }
+            else if c == '\'' && self.is_label_start() {
+                self.consume();
+                self.consume_identifier();
+                kind = TokenKind::Label;
+            }
+            else if c == '\'' {
+                kind = TokenKind::Char(self.consume_char());
+            }
             else if Self::is_string_start(&c) {
-                let string_literal = self.consume_string();
-                kind = TokenKind::String(string_literal);
+                let fragments = self.consume_string();
+                kind = TokenKind::String { fragments, kind: StringKind::Normal };
             } 
             
             else if Self::is_identifier_start(&c){
                 let identifier = self.consume_identifier();
-                kind = match identifier.as_str() {
-                    "let" => TokenKind::Let,
-                    "if" => TokenKind::If,
-                    "else" => TokenKind::Else,
-                    "true" => TokenKind::True,
-                    "false" => TokenKind::False,
-                    "while" => TokenKind::While,
-                    "func" => TokenKind::Func,
-                    "return" => TokenKind::Return,
-                    _ => TokenKind::Identifier,
-                }
-
+                kind = TokenKind::keyword(&identifier).unwrap_or_else(|| {
+                    self.lint_identifier(identifier, start);
+                    TokenKind::Identifier
+                });
             } else  {
                 kind = self.consume_punctuation();
             }
@@ -198,29 +394,78 @@ impl<'a> Lexer<'a> {
     fn consume_punctuation(&mut self) -> TokenKind {
         let c = self.consume().unwrap();
         match c {
-            '+' => TokenKind::Plus,
-            '-' => self.lex_potential_double_char_operator('>', TokenKind::Minus, TokenKind::Arrow),
-            '*' => {
-                self.lex_potential_double_char_operator('*', TokenKind::Asterisk, TokenKind::DoubleAsterisk)
+            '+' => self.lex_potential_double_char_operator('=', TokenKind::Plus, TokenKind::PlusEquals),
+            '-' => match self.current_char() {
+                Some('>') => {
+                    self.consume();
+                    TokenKind::Arrow
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::Minus, TokenKind::MinusEquals),
+            },
+            '*' => match self.current_char() {
+                Some('*') => {
+                    self.consume();
+                    self.lex_potential_double_char_operator('=', TokenKind::DoubleAsterisk, TokenKind::DoubleAsteriskEquals)
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::Asterisk, TokenKind::AsteriskEquals),
+            },
+            '/' => match self.current_char() {
+                Some('/') => self.consume_line_comment(),
+                Some('*') => self.consume_block_comment(),
+                _ => self.lex_potential_double_char_operator('=', TokenKind::Slash, TokenKind::SlashEquals),
+            },
+            '%' => self.lex_potential_double_char_operator('=', TokenKind::Percent, TokenKind::PercentEquals),
+            '.' => match self.current_char() {
+                Some('.') => {
+                    self.consume();
+                    self.lex_potential_double_char_operator('=', TokenKind::DotDot, TokenKind::DotDotEquals)
+                }
+                _ => TokenKind::Dot,
             },
-            '/' => TokenKind::Slash,
             '(' => TokenKind::LeftParen,
             ')' => TokenKind::RightParen,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
             '=' => {
                 self.lex_potential_double_char_operator('=', TokenKind::Equals, TokenKind::EqualsEquals)
             },
-            '&' => TokenKind::Ampersand,
-            '|' => TokenKind::Pipe,
-            '^' => TokenKind::Caret,
+            '&' => match self.current_char() {
+                Some('&') => {
+                    self.consume();
+                    TokenKind::AmpersandAmpersand
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::Ampersand, TokenKind::AmpersandEquals),
+            },
+            '|' => match self.current_char() {
+                Some('|') => {
+                    self.consume();
+                    TokenKind::PipePipe
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::Pipe, TokenKind::PipeEquals),
+            },
+            '^' => self.lex_potential_double_char_operator('=', TokenKind::Caret, TokenKind::CaretEquals),
             '~' => TokenKind::Tilde,
-            '>' => {
-                self.lex_potential_double_char_operator('=', TokenKind::GreaterThan, TokenKind::GreaterThanEquals)
+            '>' => match (self.current_char(), self.peek_char(1)) {
+                (Some('>'), Some('>')) => {
+                    self.consume();
+                    self.consume();
+                    self.lex_potential_double_char_operator('=', TokenKind::GreaterThanGreaterThanGreaterThan, TokenKind::GreaterThanGreaterThanGreaterThanEquals)
+                }
+                (Some('>'), _) => {
+                    self.consume();
+                    self.lex_potential_double_char_operator('=', TokenKind::GreaterThanGreaterThan, TokenKind::GreaterThanGreaterThanEquals)
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::GreaterThan, TokenKind::GreaterThanEquals),
             },
-            '<' => {
-                self.lex_potential_double_char_operator('=', TokenKind::LessThan, TokenKind::LessThanEquals)
+            '<' => match self.current_char() {
+                Some('<') => {
+                    self.consume();
+                    self.lex_potential_double_char_operator('=', TokenKind::LessThanLessThan, TokenKind::LessThanLessThanEquals)
+                }
+                _ => self.lex_potential_double_char_operator('=', TokenKind::LessThan, TokenKind::LessThanEquals),
             },
             '!' => {
-                self.lex_potential_double_char_operator('=', TokenKind::Bad, TokenKind::BangEquals)
+                self.lex_potential_double_char_operator('=', TokenKind::Bang, TokenKind::BangEquals)
             },
             '{' => {
                 TokenKind::OpenBrace
@@ -242,6 +487,43 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn consume_line_comment(&mut self) -> TokenKind {
+        while let Some(c) = self.current_char() {
+            if c == '\n' {
+                break;
+            }
+            self.consume();
+        }
+        TokenKind::LineComment
+    }
+
+    /// Consumes a block comment whose opening '/' was already consumed.
+    /// Block comments nest, so `/* a /* b */ c */` is a single comment.
+    fn consume_block_comment(&mut self) -> TokenKind {
+        let start = self.current_pos - 1;
+        self.consume(); // Consume the '*'
+        let mut depth = 1;
+        while depth > 0 {
+            match self.consume() {
+                Some('/') if self.current_char() == Some('*') => {
+                    self.consume();
+                    depth += 1;
+                }
+                Some('*') if self.current_char() == Some('/') => {
+                    self.consume();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => {
+                    let span = TextSpan::new(start, start + 2, "/*".to_string());
+                    self.diagnostics_bag.borrow_mut().report_unterminated_block_comment(&span);
+                    break;
+                }
+            }
+        }
+        TokenKind::BlockComment
+    }
+
     fn lex_potential_double_char_operator(&mut self, expected: char, one_char_kind: TokenKind, double_char_kind: TokenKind) -> TokenKind {
         if let Some(next) = self.current_char() {
             if next == expected {
@@ -256,19 +538,57 @@ impl<'a> Lexer<'a> {
     }
 
     fn is_number_start(c: &char) -> bool {
-        c.is_digit(10)
+        c.is_ascii_digit()
     }
 
     fn is_string_start(c: &char) -> bool {
-        *c == '"' || *c == '\''
+        *c == '"'
+    }
+
+    /// Whether the `'` at the cursor starts a label like `'outer` rather than a
+    /// character literal like `'o'`.
+    fn is_label_start(&self) -> bool {
+        if !self.peek_char(1).is_some_and(|c| Self::is_identifier_start(&c)) {
+            return false;
+        }
+        let mut offset = 2;
+        while self.peek_char(offset).is_some_and(|c| Self::is_identifier_continue(&c)) {
+            offset += 1;
+        }
+        self.peek_char(offset) != Some('\'')
     }
 
     fn is_identifier_start(c: &char) -> bool {
-        c.is_alphabetic()
+        *c == '_' || c.is_xid_start()
     }
 
-    fn is_decimal(c: &char) -> bool {
-       *c == 'd'|| *c == '.'
+    fn is_identifier_continue(c: &char) -> bool {
+        c.is_xid_continue()
+    }
+
+    /// Checks whether a float literal such as `56.2`, `.5`, `1e-3` or the deprecated
+    /// `d56.2` starts at the current position. `1..2`, `1.foo` and `d2` are not floats.
+    fn is_decimal_start(&self) -> bool {
+        let has_prefix = self.current_char() == Some('d');
+        let mut offset = if has_prefix { 1 } else { 0 };
+        let is_digit_at = |offset: usize| self.peek_char(offset).is_some_and(|c| c.is_ascii_digit());
+        if !(is_digit_at(offset) || self.peek_char(offset) == Some('.') && is_digit_at(offset + 1)) {
+            return false;
+        }
+        if self.peek_char(offset) == Some('0') && matches!(self.peek_char(offset + 1), Some('x' | 'X' | 'o' | 'O' | 'b' | 'B')) {
+            return false;
+        }
+        while self.peek_char(offset).is_some_and(|c| c.is_ascii_digit() || c == '_') {
+            offset += 1;
+        }
+        match self.peek_char(offset) {
+            Some('.') => is_digit_at(offset + 1),
+            Some('e' | 'E') if !has_prefix => match self.peek_char(offset + 1) {
+                Some('+' | '-') => is_digit_at(offset + 2),
+                _ => is_digit_at(offset + 1),
+            },
+            _ => false,
+        }
     }
 
     fn is_whitespace(c: &char) -> bool {
@@ -276,23 +596,40 @@ impl<'a> Lexer<'a> {
     }
 
     fn current_char(&self) -> Option<char> {
-        self.input.chars().nth(self.current_pos)
+        self.remaining().chars().next()
+    }
+
+    /// Looks `offset` characters ahead. Lookahead is only ever a few characters, so
+    /// walking the remaining input is cheap.
+    fn peek_char(&self, offset: usize) -> Option<char> {
+        self.remaining().chars().nth(offset)
+    }
+
+    /// The not yet consumed input. Empty once the end of file was reached.
+    fn remaining(&self) -> &'a str {
+        self.input.get(self.current_pos..).unwrap_or("")
+    }
+
+    fn consume_n(&mut self, count: usize) {
+        for _ in 0..count {
+            self.consume();
+        }
     }
 
     fn consume(&mut self) -> Option<char> {
         if self.current_pos >= self.input.len() {
             return None;
         }
-        let c = self.current_char();
-        self.current_pos += 1;
+        let c = self.current_char()?;
+        self.current_pos += c.len_utf8();
 
-        c
+        Some(c)
     }
 
     fn consume_identifier(&mut self) -> String {
         let mut identifier = String::new();
         while let Some(c) = self.current_char() {
-            if Self::is_identifier_start(&c) {
+            if Self::is_identifier_continue(&c) {
                 self.consume().unwrap();
                 identifier.push(c);
             } else {
@@ -302,86 +639,620 @@ impl<'a> Lexer<'a> {
         identifier
     }
 
-    fn consume_number(&mut self) -> i64 {
-        let mut number: i64 = 0;
+    /// Warns about identifiers mixing several scripts and about non-ASCII identifiers that
+    /// look like another identifier in the file, e.g. a Cyrillic 'а' in place of a Latin 'a'.
+    fn lint_identifier(&mut self, identifier: String, start: usize) {
+        if self.linted_identifiers.contains(&identifier) {
+            return;
+        }
+        let span = self.span_from(start);
+        if !identifier.as_str().is_single_script() {
+            self.diagnostics_bag.borrow_mut().report_mixed_script_identifier(&span);
+        }
+        let skeleton: String = unicode_security::skeleton(&identifier).collect();
+        match self.identifier_skeletons.get(&skeleton) {
+            Some(other) if !(other.is_ascii() && identifier.is_ascii()) => {
+                self.diagnostics_bag.borrow_mut().report_confusable_identifier(&span, other);
+            }
+            Some(_) => {}
+            None => {
+                self.identifier_skeletons.insert(skeleton, identifier.clone());
+            }
+        }
+        self.linted_identifiers.insert(identifier);
+    }
+
+    /// Consumes an integer literal. `0x`, `0o` and `0b` select the radix, `_` may be
+    /// used to separate digits and a suffix like `u8` selects the type. Literals that
+    /// don't fit into a `u64` are reported and evaluate to 0, the resolver checks them
+    /// against the range of their type.
+    fn consume_number(&mut self) -> (u64, Option<IntegerType>) {
+        let start = self.current_pos;
+        let radix = match (self.current_char(), self.peek_char(1)) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('o' | 'O')) => 8,
+            (Some('0'), Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.consume_n(2); // Consume the prefix
+        }
+        let mut number: Option<u64> = Some(0);
+        let mut has_digits = false;
         while let Some(c) = self.current_char() {
-            if c.is_digit(10) {
-                self.consume().unwrap();
-                number = number * 10 + c.to_digit(10).unwrap() as i64;
-            } else {
-                break;
+            if c == '_' {
+                self.consume();
+                continue;
             }
+            let digit = match c.to_digit(radix) {
+                Some(digit) => digit,
+                None if c.is_ascii_digit() => {
+                    let digit_start = self.current_pos;
+                    self.consume();
+                    let span = self.span_from(digit_start);
+                    self.diagnostics_bag.borrow_mut().report_invalid_digit(&span, radix);
+                    continue;
+                }
+                None => break,
+            };
+            self.consume();
+            has_digits = true;
+            number = number
+                .and_then(|number| number.checked_mul(radix as u64))
+                .and_then(|number| number.checked_add(digit as u64));
+        }
+        let digits_span = self.span_from(start);
+        let suffix = self.consume_number_suffix();
+        if !has_digits {
+            self.diagnostics_bag.borrow_mut().report_missing_digits(&digits_span);
+            return (0, suffix);
+        }
+        let number = number.unwrap_or_else(|| {
+            let ty = suffix.map_or(Type::Int, Type::integer);
+            self.diagnostics_bag.borrow_mut().report_literal_out_of_range(&self.span_from(start), &ty);
+            0
+        });
+        (number, suffix)
+    }
+
+    /// Consumes the type suffix of an integer literal, an `i` or `u` followed by a
+    /// digit or `usize`.
+    fn consume_number_suffix(&mut self) -> Option<IntegerType> {
+        let is_suffix = match (self.current_char(), self.peek_char(1)) {
+            (Some('i' | 'u'), Some(c)) if c.is_ascii_digit() => true,
+            (Some('u'), Some('s')) => true,
+            _ => false,
+        };
+        if !is_suffix {
+            return None;
+        }
+        let start = self.current_pos;
+        let suffix = self.consume_identifier();
+        let integer = IntegerType::from_name(&suffix);
+        if integer.is_none() {
+            let span = self.span_from(start);
+            self.diagnostics_bag.borrow_mut().report_unknown_integer_suffix(&span);
         }
-        number
+        integer
     }
 
-    fn consume_string(&mut self) -> String {
+    fn consume_string(&mut self) -> Vec<StringFragment> {
+        let mut fragments = Vec::new();
         let mut string = String::new();
+        let start = self.current_pos;
         let quote_char = self.consume().unwrap(); // Consume the opening quote character
 
+        loop {
+            match self.current_char() {
+                None => {
+                    let span = TextSpan::new(start, start + 1, quote_char.to_string());
+                    self.diagnostics_bag.borrow_mut().report_unterminated_string(&span);
+                    break;
+                }
+                Some(c) if c == quote_char => {
+                    self.consume().unwrap(); // Consume the closing quote character
+                    break;
+                }
+                Some('\\') => {
+                    if let Some(c) = self.consume_escape_sequence() {
+                        string.push(c);
+                    }
+                }
+                Some('$') if self.peek_char(1) == Some('{') => {
+                    if !string.is_empty() {
+                        fragments.push(StringFragment::Literal { value: std::mem::take(&mut string) });
+                    }
+                    let (tokens, is_terminated) = self.consume_interpolation();
+                    fragments.push(StringFragment::Interpolation { tokens });
+                    if !is_terminated {
+                        // The unterminated interpolation has already been reported
+                        break;
+                    }
+                }
+                Some(c) => {
+                    string.push(c);
+                    self.consume().unwrap();
+                }
+            }
+        }
+        if !string.is_empty() || fragments.is_empty() {
+            fragments.push(StringFragment::Literal { value: string });
+        }
+        fragments
+    }
+
+    /// Consumes a character literal like `'a'` or `'\n'`. Invalid literals are reported
+    /// and lexed as U+FFFD.
+    fn consume_char(&mut self) -> char {
+        let start = self.current_pos;
+        self.consume(); // Consume the opening quote
+        let character = match self.current_char() {
+            None | Some('\n') => None,
+            Some('\\') => Some(self.consume_escape_sequence()),
+            Some(c) => {
+                self.consume();
+                Some(Some(c))
+            }
+        };
+        if self.current_char() == Some('\'') {
+            self.consume();
+            return character.flatten().unwrap_or(char::REPLACEMENT_CHARACTER);
+        }
+        // Look for the closing quote on the same line, in case this was meant as a string
         while let Some(c) = self.current_char() {
-            if c == quote_char {
-                self.consume().unwrap(); // Consume the closing quote character
+            if c == '\n' {
                 break;
             }
-            string.push(c);
-            self.consume().unwrap();
+            self.consume();
+            if c == '\'' {
+                let span = self.span_from(start);
+                self.diagnostics_bag.borrow_mut().report_invalid_char_literal(&span);
+                return char::REPLACEMENT_CHARACTER;
+            }
         }
-        string
+        let span = TextSpan::new(start, start + 1, "'".to_string());
+        self.diagnostics_bag.borrow_mut().report_unterminated_char(&span);
+        char::REPLACEMENT_CHARACTER
     }
 
-    
-    fn consume_decimal(&mut self) -> f64 {
-        // Check for the 'd' prefix
-        let is_float = if let Some('d') = self.current_char() {
-            self.consume().unwrap(); // Consume the 'd'
-            true
-        } else {
-            eprintln!("Expected 'd' after float literal");
-            process::exit(1);
-        };
-    
-        // Parse the float literal without the 'f' prefix
-        let float_literal = self.parse_float_literal();
-        // Adjust the result based on whether it's a float or not
-        if is_float {
-            float_literal
-        } else {
-            float_literal as f64
+    /// Lexes the tokens of a `${...}` interpolation up to its matching closing brace,
+    /// which is emitted as a `StringEnd` token. Returns whether the closing brace was found.
+    fn consume_interpolation(&mut self) -> (Vec<Token>, bool) {
+        let start = self.current_pos;
+        self.consume(); // Consume the '$'
+        self.consume(); // Consume the '{'
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+        while self.current_char().is_some() {
+            let mut token = self.next_token().unwrap();
+            match token.kind {
+                TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseBrace if depth == 0 => {
+                    token.kind = TokenKind::StringEnd;
+                    tokens.push(token);
+                    return (tokens, true);
+                }
+                TokenKind::CloseBrace => depth -= 1,
+                _ => {}
+            }
+            tokens.push(token);
         }
+        let span = TextSpan::new(start, start + 2, "${".to_string());
+        self.diagnostics_bag.borrow_mut().report_unterminated_interpolation(&span);
+        tokens.push(Token::new(TokenKind::StringEnd, TextSpan::new(self.current_pos, self.current_pos, String::new())));
+        (tokens, false)
     }
-    
-    fn parse_float_literal(&mut self) -> f64 {
+
+    /// Consumes a `''` string. Escapes are `'''` for `''`, `''$` for `$` and `''\` followed
+    /// by a regular escape sequence; a lone backslash is an ordinary character. The raw
+    /// contents are then dedented by `dedent_indented_string`.
+    fn consume_indented_string(&mut self) -> Vec<StringFragment> {
+        let mut chars = Vec::new();
         let start = self.current_pos;
+        self.consume_n(2); // Consume the opening quotes
 
-        // Consume the digits before the decimal point
-        while let Some(c) = self.current_char() {
-            if c.is_digit(10) {
-                self.consume().unwrap();
+        loop {
+            match self.current_char() {
+                None => {
+                    let span = TextSpan::new(start, start + 2, "''".to_string());
+                    self.diagnostics_bag.borrow_mut().report_unterminated_string(&span);
+                    break;
+                }
+                Some('\'') if self.peek_char(1) == Some('\'') => {
+                    match self.peek_char(2) {
+                        Some('\'') => {
+                            self.consume_n(3);
+                            chars.push(IndentedChar::Escaped('\''));
+                            chars.push(IndentedChar::Escaped('\''));
+                        }
+                        Some('$') => {
+                            self.consume_n(3);
+                            chars.push(IndentedChar::Escaped('$'));
+                        }
+                        Some('\\') => {
+                            self.consume_n(2);
+                            if let Some(c) = self.consume_escape_sequence() {
+                                chars.push(IndentedChar::Escaped(c));
+                            }
+                        }
+                        _ => {
+                            self.consume_n(2); // Consume the closing quotes
+                            break;
+                        }
+                    }
+                }
+                Some('$') if self.peek_char(1) == Some('{') => {
+                    let (tokens, is_terminated) = self.consume_interpolation();
+                    chars.push(IndentedChar::Interpolation(tokens));
+                    if !is_terminated {
+                        break;
+                    }
+                }
+                Some(c) => {
+                    chars.push(IndentedChar::Raw(c));
+                    self.consume();
+                }
+            }
+        }
+        Self::dedent_indented_string(chars)
+    }
+
+    /// Applies the layout rules of `''` strings: a first line containing only whitespace
+    /// is dropped, as is the whitespace before the closing quotes on the last line, and
+    /// the smallest indentation (in spaces) of all non-blank lines is stripped from every
+    /// line. Escaped characters and interpolations always count as content.
+    fn dedent_indented_string(chars: Vec<IndentedChar>) -> Vec<StringFragment> {
+        let is_blank = |c: &IndentedChar| matches!(c, IndentedChar::Raw(' ' | '\t'));
+        let mut lines: Vec<Vec<IndentedChar>> = vec![Vec::new()];
+        for c in chars {
+            if let IndentedChar::Raw('\n') = c {
+                lines.push(Vec::new());
             } else {
-                break;
+                lines.last_mut().unwrap().push(c);
+            }
+        }
+        if lines.len() > 1 && lines[0].iter().all(is_blank) {
+            lines.remove(0);
+        }
+        let last_line = lines.last_mut().unwrap();
+        if last_line.iter().all(is_blank) {
+            last_line.clear();
+        }
+        let indentation = lines.iter()
+            .filter(|line| !line.iter().all(is_blank))
+            .map(|line| line.iter().take_while(|c| matches!(c, IndentedChar::Raw(' '))).count())
+            .min()
+            .unwrap_or(0);
+
+        let mut fragments = Vec::new();
+        let mut string = String::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            if index > 0 {
+                string.push('\n');
+            }
+            let line_indentation = line.iter().take(indentation).take_while(|c| matches!(c, IndentedChar::Raw(' '))).count();
+            for c in line.into_iter().skip(line_indentation) {
+                match c {
+                    IndentedChar::Raw(c) | IndentedChar::Escaped(c) => string.push(c),
+                    IndentedChar::Interpolation(tokens) => {
+                        if !string.is_empty() {
+                            fragments.push(StringFragment::Literal { value: std::mem::take(&mut string) });
+                        }
+                        fragments.push(StringFragment::Interpolation { tokens });
+                    }
+                }
             }
         }
+        if !string.is_empty() || fragments.is_empty() {
+            fragments.push(StringFragment::Literal { value: string });
+        }
+        fragments
+    }
+
+    /// Consumes an escape sequence starting at the current backslash and returns the
+    /// character it stands for, or `None` if the sequence is invalid.
+    fn consume_escape_sequence(&mut self) -> Option<char> {
+        let start = self.current_pos;
+        self.consume(); // Consume the backslash
+        let escaped = match self.consume()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '$' => '$',
+            'u' => return self.consume_unicode_escape(start),
+            _ => {
+                let span = self.span_from(start);
+                self.diagnostics_bag.borrow_mut().report_unknown_escape_sequence(&span);
+                return None;
+            }
+        };
+        Some(escaped)
+    }
+
+    /// Consumes the `{XXXX}` part of a `\u{XXXX}` escape, with one to six hex digits.
+    fn consume_unicode_escape(&mut self, start: usize) -> Option<char> {
+        let mut code_point = None;
+        if self.current_char() == Some('{') {
+            self.consume();
+            let mut digits = String::new();
+            while let Some(c) = self.current_char() {
+                if !c.is_ascii_hexdigit() {
+                    break;
+                }
+                digits.push(c);
+                self.consume();
+            }
+            if self.current_char() == Some('}') {
+                self.consume();
+                if (1..=6).contains(&digits.len()) {
+                    code_point = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                }
+            }
+        }
+        if code_point.is_none() {
+            let span = self.span_from(start);
+            self.diagnostics_bag.borrow_mut().report_invalid_unicode_escape(&span);
+        }
+        code_point
+    }
+
+    fn span_from(&self, start: usize) -> TextSpan {
+        TextSpan::new(start, self.current_pos, self.input[start..self.current_pos].to_string())
+    }
+
+    
+    fn consume_decimal(&mut self) -> f64 {
+        let start = self.current_pos;
+        let has_prefix = self.current_char() == Some('d');
+        if has_prefix {
+            self.consume(); // Consume the 'd'
+        }
+        let float_literal = self.parse_float_literal(start);
+        if has_prefix {
+            let span = self.span_from(start);
+            self.diagnostics_bag.borrow_mut().report_deprecated_decimal_prefix(&span);
+        }
+        float_literal
+    }
+
+    fn parse_float_literal(&mut self, literal_start: usize) -> f64 {
+        let start = self.current_pos;
+
+        // Consume the digits before the decimal point
+        self.consume_decimal_digits();
     
         // Check for the decimal point
         if let Some('.') = self.current_char() {
             self.consume().unwrap(); // Consume the decimal point
     
             // Consume the digits after the decimal point
-            while let Some(c) = self.current_char() {
-                if c.is_digit(10) {
-                    self.consume().unwrap();
-                } else {
-                    break;
-                }
+            self.consume_decimal_digits();
+        }
+
+        // Check for an exponent such as `e10`, `E+3` or `e-7`
+        if let Some('e' | 'E') = self.current_char() {
+            let has_exponent = match self.peek_char(1) {
+                Some('+' | '-') => self.peek_char(2).is_some_and(|c| c.is_ascii_digit()),
+                Some(c) => c.is_ascii_digit(),
+                None => false,
+            };
+            if has_exponent {
+                self.consume_n(2);
+                self.consume_decimal_digits();
             }
         }
     
         // Parse the consumed characters as a float
-        let literal = &self.input[start..self.current_pos];
-        literal.parse().unwrap_or(0.0)
+        let literal = self.input[start..self.current_pos].replace('_', "");
+        let value = literal.parse().unwrap_or(0.0);
+        if f64::is_infinite(value) {
+            let span = self.span_from(literal_start);
+            self.diagnostics_bag.borrow_mut().report_literal_out_of_range(&span, &Type::Float);
+            return 0.0;
+        }
+        value
     }
-    
-    
-    
-}
\ No newline at end of file
+
+    fn consume_decimal_digits(&mut self) {
+        while let Some(c) = self.current_char() {
+            if c.is_ascii_digit() || c == '_' {
+                self.consume().unwrap();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    use crate::ast::lexer::{Lexer, Token, TokenKind, KEYWORDS};
+    use crate::diagnostics::DiagnosticsBag;
+    use crate::typings::IntegerType;
+
+    fn lex(input: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(input, Rc::new(RefCell::new(DiagnosticsBag::new())));
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    #[test]
+    fn should_produce_byte_offset_spans_for_non_ascii_input() {
+        let input = "let größe = \"日本\" + ñ";
+        let tokens: Vec<Token> = lex(input).into_iter().filter(|token| !token.kind.is_trivia()).collect();
+
+        let literals: Vec<&str> = tokens.iter().map(|token| &input[token.span.start..token.span.end]).collect();
+        assert_eq!(literals, vec!["let", "größe", "=", "\"日本\"", "+", "ñ", ""]);
+        for token in tokens.iter().filter(|token| token.kind != TokenKind::Eof) {
+            assert_eq!(token.span.literal, input[token.span.start..token.span.end]);
+        }
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Eof);
+    }
+
+    #[test]
+    fn should_lex_every_keyword_in_the_keyword_table() {
+        for (keyword, kind) in KEYWORDS {
+            assert_eq!(&lex(keyword)[0].kind, kind);
+            assert!(kind.is_keyword());
+        }
+        assert_eq!(TokenKind::keyword("whiles"), None);
+        assert_eq!(lex("let_x")[0].kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn should_lex_the_longest_shift_operator() {
+        let kinds: Vec<TokenKind> = lex("a>>>b>>c<<d<=e>=f%g").into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Identifier)
+            .collect();
+        assert_eq!(kinds, vec![
+            TokenKind::GreaterThanGreaterThanGreaterThan,
+            TokenKind::GreaterThanGreaterThan,
+            TokenKind::LessThanLessThan,
+            TokenKind::LessThanEquals,
+            TokenKind::GreaterThanEquals,
+            TokenKind::Percent,
+            TokenKind::Eof,
+        ]);
+    }
+
+    #[test]
+    fn should_lex_compound_assignment_operators() {
+        let kinds: Vec<TokenKind> = lex("+= -= -> *= **= ** /= %= &= && |= ^= <<= >>= >>>=").into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect();
+        assert_eq!(kinds, vec![
+            TokenKind::PlusEquals,
+            TokenKind::MinusEquals,
+            TokenKind::Arrow,
+            TokenKind::AsteriskEquals,
+            TokenKind::DoubleAsteriskEquals,
+            TokenKind::DoubleAsterisk,
+            TokenKind::SlashEquals,
+            TokenKind::PercentEquals,
+            TokenKind::AmpersandEquals,
+            TokenKind::AmpersandAmpersand,
+            TokenKind::PipeEquals,
+            TokenKind::CaretEquals,
+            TokenKind::LessThanLessThanEquals,
+            TokenKind::GreaterThanGreaterThanEquals,
+            TokenKind::GreaterThanGreaterThanGreaterThanEquals,
+            TokenKind::Eof,
+        ]);
+    }
+
+    #[test]
+    fn should_not_lex_range_operators_as_decimal_points() {
+        let kinds: Vec<TokenKind> = lex("0..5 1..=2.5").into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Number(0, None),
+            TokenKind::DotDot,
+            TokenKind::Number(5, None),
+            TokenKind::Number(1, None),
+            TokenKind::DotDotEquals,
+            TokenKind::Decimal(2.5),
+            TokenKind::Eof,
+        ]);
+    }
+
+    #[test]
+    fn should_lex_labels_apart_from_char_literals() {
+        let kinds: Vec<TokenKind> = lex("'outer: 'o' '\\'' '\\u{e9}' 'é'").into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Label,
+            TokenKind::Colon,
+            TokenKind::Char('o'),
+            TokenKind::Char('\''),
+            TokenKind::Char('é'),
+            TokenKind::Char('é'),
+            TokenKind::Eof,
+        ]);
+    }
+
+    #[test]
+    fn should_lex_integer_suffixes() {
+        let kinds: Vec<TokenKind> = lex("255u8 0xFFi64 1_000usize 7 2ins").into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Number(255, Some(IntegerType::U8)),
+            TokenKind::Number(255, Some(IntegerType::I64)),
+            TokenKind::Number(1000, Some(IntegerType::Usize)),
+            TokenKind::Number(7, None),
+            TokenKind::Number(2, None),
+            TokenKind::Identifier,
+            TokenKind::Eof,
+        ]);
+    }
+
+    #[test]
+    fn should_reproduce_the_input_from_lossless_tokens() {
+        let input = "\
+        // leading comment\r
+        func add(a: int, /* inline */ b: int) -> int {
+            return a + b // trailing comment
+        }
+
+        let größe = \"${add(1, 2)} \" + ''
+          indented
+        ''   \n";
+        let tokens = Lexer::new(input, Rc::new(RefCell::new(DiagnosticsBag::new()))).lex_lossless();
+
+        let text: String = tokens.iter().map(|token| token.full_text()).collect();
+        assert_eq!(text, input);
+        assert!(tokens.iter().all(|token| !token.kind.is_trivia()));
+    }
+
+    #[test]
+    fn should_attach_trivia_up_to_the_newline_to_the_previous_token() {
+        let input = "a // note\n  b";
+        let tokens = Lexer::new(input, Rc::new(RefCell::new(DiagnosticsBag::new()))).lex_lossless();
+
+        let trailing: Vec<&str> = tokens[0].trailing_trivia.iter().map(|trivia| trivia.span.literal.as_str()).collect();
+        let leading: Vec<&str> = tokens[1].leading_trivia.iter().map(|trivia| trivia.span.literal.as_str()).collect();
+        assert_eq!(trailing, vec![" ", "// note", "\n"]);
+        assert_eq!(leading, vec![" ", " "]);
+    }
+
+    /// The fastest of a few runs, so that a slow run doesn't fail the test below.
+    fn time_lex(bytes: usize) -> Duration {
+        let line = "let größe = 0xFF + 1_000 * 2.5e3 // comment\nwhile größe > 0 { größe = größe - \"ü${größe}\" }\n";
+        let input = line.repeat(bytes / line.len());
+        (0..3).map(|_| {
+            let start = Instant::now();
+            let tokens = lex(&input);
+            let elapsed = start.elapsed();
+            assert_eq!(tokens.last().unwrap().kind, TokenKind::Eof);
+            elapsed
+        }).min().unwrap()
+    }
+
+    #[test]
+    fn should_lex_in_time_linear_in_the_input_size() {
+        let two_megabytes = time_lex(2 * 1024 * 1024);
+        let four_megabytes = time_lex(4 * 1024 * 1024);
+
+        assert!(
+            four_megabytes <= two_megabytes * 5 / 2,
+            "lexing 4 MB took {:?}, more than 2.5 times the {:?} of 2 MB", four_megabytes, two_megabytes,
+        );
+    }
+}
@@ -9,17 +9,18 @@ use fusion_compiler::{Idx, idx, IdxVec};
 use printer::ASTPrinter;
 use visitor::ASTVisitor;
 
-use crate::ast::lexer::{ Token, StringFragment, StringKind };
+use crate::ast::lexer::{ Token, TokenKind, StringFragment, StringKind };
 
 use crate::compilation_unit::{FunctionIdx, VariableIdx};
 use crate::text::span::TextSpan;
-use crate::typings::Type;
+use crate::typings::{IntegerType, Type};
 
 pub mod lexer;
 pub mod parser;
 pub mod evaluator;
 pub mod visitor;
 pub mod printer;
+pub mod control_flow;
 
 idx!(StmtId);
 idx!(ExprId);
@@ -77,10 +78,47 @@ impl Ast {
             StmtKind::Let(var_decl) => {
                 var_decl.variable_idx = variable_idx;
             }
+            StmtKind::For(for_stmt) => {
+                for_stmt.variable_idx = variable_idx;
+            }
             _ => unreachable!("Cannot set variable of non-variable statement")
         }
     }
 
+    /// Records the operator a binary or compound assignment operator resolved to.
+    pub fn set_binary_operator(&mut self, expr_id: ExprId, kind: BinOpKind) {
+        let expr = self.query_expr_mut(expr_id);
+        match &mut expr.kind {
+            ExprKind::Binary(binary_expr) => {
+                binary_expr.operator.kind = kind;
+            }
+            ExprKind::Assignment(AssignExpr { operator: Some(operator), .. }) => {
+                operator.kind = kind;
+            }
+            _ => unreachable!("Cannot set operator of non-binary expression")
+        }
+    }
+
+    pub fn set_unary_operator(&mut self, expr_id: ExprId, kind: UnOpKind) {
+        let expr = self.query_expr_mut(expr_id);
+        match &mut expr.kind {
+            ExprKind::Unary(unary_expr) => {
+                unary_expr.operator.kind = kind;
+            }
+            _ => unreachable!("Cannot set operator of non-unary expression")
+        }
+    }
+
+    pub fn set_function_for_item(&mut self, item_id: ItemId, function_idx: FunctionIdx) {
+        let item = &mut self.items[item_id];
+        match &mut item.kind {
+            ItemKind::Function(func_decl) => {
+                func_decl.idx = function_idx;
+            }
+            _ => unreachable!("Cannot set function of non-function item")
+        }
+    }
+
     pub fn set_type(&mut self, expr_id: ExprId, ty: Type) {
         let expr = &mut self.expressions[expr_id];
         expr.ty = ty;
@@ -105,8 +143,41 @@ impl Ast {
         self.expr_from_kind(ExprKind::If(IfExpr { if_keyword, condition, then_branch: then, else_branch: else_statement }))
     }
 
-    pub fn while_statement(&mut self, while_keyword: Token, condition: ExprId, body: ExprId) -> &Stmt {
-        self.stmt_from_kind(StmtKind::While(WhileStmt { while_keyword, condition, body }))
+    pub fn while_statement(&mut self, label: Option<LoopLabel>, while_keyword: Token, condition: ExprId, body: ExprId) -> &Stmt {
+        self.stmt_from_kind(StmtKind::While(WhileStmt { label, while_keyword, condition, body }))
+    }
+
+    pub fn loop_expression(&mut self, label: Option<LoopLabel>, loop_keyword: Token, body: ExprId) -> &Expr {
+        self.expr_from_kind(ExprKind::Loop(LoopExpr { label, loop_keyword, body }))
+    }
+
+    pub fn char_expression(&mut self, token: Token, value: char) -> &Expr {
+        self.expr_from_kind(ExprKind::Char(CharExpr { value, token }))
+    }
+
+    pub fn cast_expression(&mut self, expr: ExprId, as_keyword: Token, type_name: Token) -> &Expr {
+        self.expr_from_kind(ExprKind::Cast(CastExpr { expr, as_keyword, type_name }))
+    }
+
+    pub fn index_expression(&mut self, target: ExprId, left_bracket: Token, index: ExprId, right_bracket: Token) -> &Expr {
+        self.expr_from_kind(ExprKind::Index(IndexExpr { target, left_bracket, index, right_bracket }))
+    }
+
+    pub fn slice_expression(&mut self, target: ExprId, left_bracket: Token, start: ExprId, range_operator: Token, end: ExprId, right_bracket: Token) -> &Expr {
+        self.expr_from_kind(ExprKind::Slice(SliceExpr { target, left_bracket, start, range_operator, end, right_bracket }))
+    }
+
+    pub fn break_statement(&mut self, break_keyword: Token, label: Option<Token>, value: Option<ExprId>) -> &Stmt {
+        self.stmt_from_kind(StmtKind::Break(BreakStmt { break_keyword, label, value }))
+    }
+
+    pub fn continue_statement(&mut self, continue_keyword: Token, label: Option<Token>) -> &Stmt {
+        self.stmt_from_kind(StmtKind::Continue(ContinueStmt { continue_keyword, label }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn for_statement(&mut self, label: Option<LoopLabel>, for_keyword: Token, identifier: Token, in_keyword: Token, start: ExprId, range: Option<ForRange>, body: ExprId) -> &Stmt {
+        self.stmt_from_kind(StmtKind::For(ForStmt { label, for_keyword, identifier, in_keyword, start, range, body, variable_idx: VariableIdx::new(0) }))
     }
 
     pub fn block_expression(&mut self, left_brace: Token, statements: Vec<StmtId>, right_brace: Token) -> &Expr {
@@ -117,7 +188,7 @@ impl Ast {
         self.stmt_from_kind(StmtKind::Return(ReturnStmt { return_keyword, return_value }))
     }
 
-    pub fn func_item(&mut self, func_keyword: Token, identifier: Token, parameters: Vec<FuncDeclParameter>, body: ExprId, return_type: Option<FunctionReturnTypeSyntax>, function_idx: FunctionIdx) -> &Item {
+    pub fn func_item(&mut self, func_keyword: Token, identifier: Token, parameters: Vec<FuncDeclParameter>, body: ExprId, return_type: Option<FunctionReturnTypeSyntax>) -> &Item {
         return self.item_from_kind(
             ItemKind::Function(
                 FunctionDeclaration {
@@ -126,7 +197,7 @@ impl Ast {
                     parameters,
                     body,
                     return_type,
-                    idx: function_idx,
+                    idx: FunctionIdx::new(0),
                 }
             )
         );
@@ -146,8 +217,8 @@ impl Ast {
         &self.expressions[id]
     }
 
-    pub fn number_expression(&mut self, token: Token, number: i64) -> &Expr {
-        self.expr_from_kind(ExprKind::Number(NumberExpr { number, token }))
+    pub fn number_expression(&mut self, token: Token, number: u64, suffix: Option<IntegerType>) -> &Expr {
+        self.expr_from_kind(ExprKind::Number(NumberExpr { number, suffix, token }))
     }
     
     pub fn decimal_expression(&mut self, token:Token, number: f64) -> &Expr {
@@ -158,6 +229,10 @@ impl Ast {
         self.expr_from_kind(ExprKind::String(StringExpr { string, token }))
     }
 
+    pub fn interpolated_string_expression(&mut self, token: Token, parts: Vec<InterpolatedStringPart>) -> &Expr {
+        self.expr_from_kind(ExprKind::InterpolatedString(InterpolatedStringExpr { token, parts }))
+    }
+
     pub fn binary_expression(&mut self, operator: BinOperator, left: ExprId, right: ExprId) -> &Expr {
         self.expr_from_kind(ExprKind::Binary(BinaryExpr { operator, left, right }))
     }
@@ -174,8 +249,8 @@ impl Ast {
         self.expr_from_kind(ExprKind::Unary(UnaryExpr { operator, operand }))
     }
 
-    pub fn assignment_expression(&mut self, identifier: Token, equals: Token, expression: ExprId) -> &Expr {
-        self.expr_from_kind(ExprKind::Assignment(AssignExpr { identifier, expression, equals, variable_idx: VariableIdx::new(0) }))
+    pub fn assignment_expression(&mut self, identifier: Token, equals: Token, operator: Option<BinOperator>, expression: ExprId) -> &Expr {
+        self.expr_from_kind(ExprKind::Assignment(AssignExpr { identifier, expression, equals, operator, variable_idx: VariableIdx::new(0) }))
     }
 
     pub fn boolean_expression(&mut self, token: Token, value: bool) -> &Expr {
@@ -187,7 +262,13 @@ impl Ast {
     }
 
     pub fn error_expression(&mut self, span: TextSpan) -> &Expr {
-        self.expr_from_kind(ExprKind::Error(span))
+        let id = self.expr_from_kind(ExprKind::Error(span)).id;
+        self.set_type(id, Type::Error);
+        &self.expressions[id]
+    }
+
+    pub fn error_statement(&mut self, span: TextSpan) -> &Stmt {
+        self.stmt_from_kind(StmtKind::Error(span))
     }
 
     pub fn visit(&mut self, visitor: &mut dyn ASTVisitor) {
@@ -219,6 +300,8 @@ impl Item {
 pub enum ItemKind {
     Stmt(StmtId),
     Function(FunctionDeclaration),
+    // An item the parser couldn't make sense of
+    Error(TextSpan),
 }
 
 
@@ -227,7 +310,26 @@ pub enum StmtKind {
     Expr(ExprId),
     Let(LetStmt),
     While(WhileStmt),
+    For(ForStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
     Return(ReturnStmt),
+    // A statement the parser couldn't make sense of
+    Error(TextSpan),
+}
+
+/// `break`, `break 'label` or `break value`. Only `loop` can be exited with a value.
+#[derive(Debug, Clone)]
+pub struct BreakStmt {
+    pub break_keyword: Token,
+    pub label: Option<Token>,
+    pub value: Option<ExprId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContinueStmt {
+    pub continue_keyword: Token,
+    pub label: Option<Token>,
 }
 
 #[derive(Debug, Clone)]
@@ -276,13 +378,106 @@ pub struct FunctionDeclaration {
     pub idx: FunctionIdx,
 }
 
+/// The `'label:` in front of a loop, which `break 'label` and `continue 'label` refer to.
+#[derive(Debug, Clone)]
+pub struct LoopLabel {
+    pub label: Token,
+    pub colon: Token,
+}
+
+impl LoopLabel {
+    pub fn name(&self) -> &str {
+        &self.label.span.literal
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WhileStmt {
+    pub label: Option<LoopLabel>,
     pub while_keyword: Token,
     pub condition: ExprId,
     pub body: ExprId,
 }
 
+/// `for i in start..end step n { ... }` over ints, or `for c in string { ... }` over the
+/// characters of a string. The loop variable is scoped to the body.
+#[derive(Debug, Clone)]
+pub struct ForStmt {
+    pub label: Option<LoopLabel>,
+    pub for_keyword: Token,
+    pub identifier: Token,
+    pub in_keyword: Token,
+    // The start of the range, or the string iterated over if there is no range
+    pub start: ExprId,
+    pub range: Option<ForRange>,
+    pub body: ExprId,
+    pub variable_idx: VariableIdx,
+}
+
+impl ForStmt {
+    pub fn is_inclusive(&self) -> bool {
+        self.range.as_ref().is_some_and(|range| range.range_operator.kind == TokenKind::DotDotEquals)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForRange {
+    // `..` excludes the end, `..=` includes it
+    pub range_operator: Token,
+    pub end: ExprId,
+    pub step: Option<ForStep>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForStep {
+    // `step` is a contextual keyword, lexed as an identifier
+    pub step_keyword: Token,
+    pub expr: ExprId,
+}
+
+/// `expr as type`, an explicit conversion. The target type is the type of the cast expression.
+#[derive(Debug, Clone)]
+pub struct CastExpr {
+    pub expr: ExprId,
+    pub as_keyword: Token,
+    pub type_name: Token,
+}
+
+/// `string[index]`, the character at `index`. Strings are indexed by character, not by byte.
+#[derive(Debug, Clone)]
+pub struct IndexExpr {
+    pub target: ExprId,
+    pub left_bracket: Token,
+    pub index: ExprId,
+    pub right_bracket: Token,
+}
+
+/// `string[start..end]`, the characters from `start` up to `end`.
+#[derive(Debug, Clone)]
+pub struct SliceExpr {
+    pub target: ExprId,
+    pub left_bracket: Token,
+    pub start: ExprId,
+    // `..` excludes the end, `..=` includes it
+    pub range_operator: Token,
+    pub end: ExprId,
+    pub right_bracket: Token,
+}
+
+impl SliceExpr {
+    pub fn is_inclusive(&self) -> bool {
+        self.range_operator.kind == TokenKind::DotDotEquals
+    }
+}
+
+/// `loop { ... }` runs until a `break`, whose value becomes the value of the loop.
+#[derive(Debug, Clone)]
+pub struct LoopExpr {
+    pub label: Option<LoopLabel>,
+    pub loop_keyword: Token,
+    pub body: ExprId,
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockExpr {
     pub left_brace: Token,
@@ -346,6 +541,27 @@ impl Stmt {
                 spans.push(ast.query_expr(while_stmt.body).span(ast));
                 TextSpan::combine(spans)
             }
+            StmtKind::For(for_stmt) => {
+                let spans = vec![for_stmt.for_keyword.span.clone(), ast.query_expr(for_stmt.body).span(ast)];
+                TextSpan::combine(spans)
+            }
+            StmtKind::Break(break_stmt) => {
+                let mut spans = vec![break_stmt.break_keyword.span.clone()];
+                if let Some(label) = &break_stmt.label {
+                    spans.push(label.span.clone());
+                }
+                if let Some(value) = &break_stmt.value {
+                    spans.push(ast.query_expr(*value).span(ast));
+                }
+                TextSpan::combine(spans)
+            }
+            StmtKind::Continue(continue_stmt) => {
+                let mut spans = vec![continue_stmt.continue_keyword.span.clone()];
+                if let Some(label) = &continue_stmt.label {
+                    spans.push(label.span.clone());
+                }
+                TextSpan::combine(spans)
+            }
             StmtKind::Return(return_stmt) => {
                 let mut spans = vec![return_stmt.return_keyword.span.clone()];
                 if let Some(return_value) = &return_stmt.return_value {
@@ -353,6 +569,7 @@ impl Stmt {
                 }
                 TextSpan::combine(spans)
             }
+            StmtKind::Error(span) => span.clone(),
         }
     }
 }
@@ -365,9 +582,15 @@ pub enum ExprKind {
     Decimal(
         DecimalExpr
     ),
+    Char(
+        CharExpr
+    ),
     String(
         StringExpr
     ),
+    InterpolatedString(
+        InterpolatedStringExpr
+    ),
     Binary(
         BinaryExpr
     ),
@@ -391,6 +614,10 @@ pub enum ExprKind {
     ),
     If(IfExpr),
     Block(BlockExpr),
+    Loop(LoopExpr),
+    Cast(CastExpr),
+    Index(IndexExpr),
+    Slice(SliceExpr),
     Error(
         TextSpan
     ),
@@ -422,7 +649,10 @@ pub struct BoolExpr {
 #[derive(Debug, Clone)]
 pub struct AssignExpr {
     pub identifier: Token,
+    // The `=` token, or the operator token of a compound assignment like `+=`
     pub equals: Token,
+    // The operator of a compound assignment, `a += 1` assigns `a + 1`
+    pub operator: Option<BinOperator>,
     pub expression: ExprId,
     pub variable_idx: VariableIdx,
 
@@ -431,13 +661,16 @@ pub struct AssignExpr {
 #[derive(Debug, Clone)]
 pub enum UnOpKind {
     Minus,
+    // `Minus` on a float, resolved by the type checker
+    MinusDecimal,
     BitwiseNot,
+    LogicalNot,
 }
 
 #[derive(Debug, Clone)]
 pub struct UnOperator {
     pub(crate) kind: UnOpKind,
-    token: Token,
+    pub(crate) token: Token,
 }
 
 impl UnOperator {
@@ -465,6 +698,8 @@ impl VarExpr {
 }
 
 
+/// The parser only produces the `int` operators, the resolver replaces them with the
+/// `*Decimal`, `*Char` and `*String` variants when the operands are floats, chars or strings.
 #[derive(Debug, Clone)]
 pub enum BinOpKind {
     // Arithmetic
@@ -472,18 +707,44 @@ pub enum BinOpKind {
     Minus,
     Multiply,
     Divide,
+    // Truncated remainder, the result takes the sign of the dividend: `-7 % 3 == -1`
+    Modulo,
     Power,
     //float arithmetic
     PlusDecimal,
     MinusDecimal,
     MultiplyDecimal,
     DivideDecimal,
+    //float comparisons
+    LessThanDecimal,
+    LessThanOrEqualDecimal,
+    GreaterThanDecimal,
+    GreaterThanOrEqualDecimal,
+    //char comparisons, by code point
+    LessThanChar,
+    LessThanOrEqualChar,
+    GreaterThanChar,
+    GreaterThanOrEqualChar,
     //string arithmetic
     PlusString,
+    //string comparisons, by character
+    EqualsString,
+    NotEqualsString,
+    LessThanString,
+    LessThanOrEqualString,
+    GreaterThanString,
+    GreaterThanOrEqualString,
     // Bitwise
     BitwiseAnd,
     BitwiseOr,
     BitwiseXor,
+    // Shifts. The shift amount is taken modulo the width of the operand type, so `1 << 64 == 1`,
+    // `1 << -1 == 1 << 63` and `1u8 << 9 == 2`
+    ShiftLeft,
+    // Arithmetic shift, the sign bit is copied in: `-8 >> 1 == -4`
+    ShiftRight,
+    // Logical shift, zeros are shifted in: `-8 >>> 60 == 15`
+    UnsignedShiftRight,
     // Relational
     Equals,
     NotEquals,
@@ -491,12 +752,85 @@ pub enum BinOpKind {
     LessThanOrEqual,
     GreaterThan,
     GreaterThanOrEqual,
+    // Logical
+    LogicalAnd,
+    LogicalOr,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinOpAssociativity {
     Left,
     Right,
+    // Chaining the operator is an error, e.g. `a < b < c`
+    None,
+}
+
+impl BinOpKind {
+    /// The operator precedence table, the single source of truth for how binary
+    /// operators bind. Higher precedences bind tighter; unary operators bind tighter
+    /// than all of them.
+    ///
+    /// The last column is the precedence of the operator emitted by the C backend,
+    /// where the bitwise operators bind looser than the comparisons.
+    fn precedence_table_entry(&self) -> (u8, BinOpAssociativity, u8) {
+        use BinOpAssociativity::{Left, None, Right};
+        match self {
+            //                                          precedence, associativity, C precedence
+            BinOpKind::Power => (10, Right, 14),
+            BinOpKind::Multiply | BinOpKind::Divide | BinOpKind::Modulo => (9, Left, 13),
+            BinOpKind::MultiplyDecimal | BinOpKind::DivideDecimal => (9, Left, 13),
+            BinOpKind::Plus | BinOpKind::Minus => (8, Left, 12),
+            BinOpKind::PlusDecimal | BinOpKind::MinusDecimal | BinOpKind::PlusString => (8, Left, 12),
+            BinOpKind::ShiftLeft | BinOpKind::ShiftRight | BinOpKind::UnsignedShiftRight => (7, Left, 11),
+            BinOpKind::BitwiseAnd => (6, Left, 8),
+            BinOpKind::BitwiseXor => (5, Left, 7),
+            BinOpKind::BitwiseOr => (4, Left, 6),
+            BinOpKind::LessThan | BinOpKind::LessThanOrEqual => (3, None, 10),
+            BinOpKind::GreaterThan | BinOpKind::GreaterThanOrEqual => (3, None, 10),
+            BinOpKind::LessThanDecimal | BinOpKind::LessThanOrEqualDecimal => (3, None, 10),
+            BinOpKind::GreaterThanDecimal | BinOpKind::GreaterThanOrEqualDecimal => (3, None, 10),
+            BinOpKind::LessThanChar | BinOpKind::LessThanOrEqualChar => (3, None, 10),
+            BinOpKind::GreaterThanChar | BinOpKind::GreaterThanOrEqualChar => (3, None, 10),
+            BinOpKind::LessThanString | BinOpKind::LessThanOrEqualString => (3, None, 10),
+            BinOpKind::GreaterThanString | BinOpKind::GreaterThanOrEqualString => (3, None, 10),
+            BinOpKind::Equals | BinOpKind::NotEquals => (3, None, 9),
+            BinOpKind::EqualsString | BinOpKind::NotEqualsString => (3, None, 9),
+            BinOpKind::LogicalAnd => (2, Left, 5),
+            BinOpKind::LogicalOr => (1, Left, 4),
+        }
+    }
+
+    pub fn precedence(&self) -> u8 {
+        self.precedence_table_entry().0
+    }
+
+    pub fn associativity(&self) -> BinOpAssociativity {
+        self.precedence_table_entry().1
+    }
+
+    pub fn c_precedence(&self) -> u8 {
+        self.precedence_table_entry().2
+    }
+
+    /// Whether a binary expression with the `operand` operator needs parentheses when it
+    /// is the `side` operand of `self`, given the `precedence` of each operator.
+    pub fn operand_needs_parentheses(&self, operand: &BinOpKind, side: OperandSide, precedence: fn(&BinOpKind) -> u8) -> bool {
+        let (parent_precedence, operand_precedence) = (precedence(self), precedence(operand));
+        if operand_precedence != parent_precedence {
+            return operand_precedence < parent_precedence;
+        }
+        match self.associativity() {
+            BinOpAssociativity::Left => side == OperandSide::Right,
+            BinOpAssociativity::Right => side == OperandSide::Left,
+            BinOpAssociativity::None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandSide {
+    Left,
+    Right,
 }
 
 #[derive(Debug, Clone)]
@@ -511,34 +845,11 @@ impl BinOperator {
     }
 
     pub fn precedence(&self) -> u8 {
-        match self.kind {
-            BinOpKind::Power => 20,
-            BinOpKind::Multiply => 19,
-            BinOpKind::Divide => 19,
-            BinOpKind::Plus => 18,
-            BinOpKind::PlusDecimal => 18,
-            BinOpKind::MinusDecimal => 18,
-            BinOpKind::MultiplyDecimal => 18,
-            BinOpKind::DivideDecimal => 18,
-            BinOpKind::PlusString => 18,
-            BinOpKind::Minus => 18,
-            BinOpKind::BitwiseAnd => 17,
-            BinOpKind::BitwiseXor => 16,
-            BinOpKind::BitwiseOr => 15,
-            BinOpKind::Equals => 30,
-            BinOpKind::NotEquals => 30,
-            BinOpKind::LessThan => 29,
-            BinOpKind::LessThanOrEqual => 29,
-            BinOpKind::GreaterThan => 29,
-            BinOpKind::GreaterThanOrEqual => 29,
-        }
+        self.kind.precedence()
     }
 
     pub fn associativity(&self) -> BinOpAssociativity {
-        match self.kind {
-            BinOpKind::Power => BinOpAssociativity::Right,
-            _ => BinOpAssociativity::Left,
-        }
+        self.kind.associativity()
     }
 }
 
@@ -551,21 +862,45 @@ pub struct BinaryExpr {
 
 #[derive(Debug, Clone)]
 pub struct NumberExpr {
-    pub number: i64,
+    pub number: u64,
+    pub suffix: Option<IntegerType>,
     pub token: Token,
 }
 
+impl NumberExpr {
+    pub fn ty(&self) -> Type {
+        self.suffix.map_or(Type::Int, Type::integer)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DecimalExpr{
     pub number: f64,
     pub token: Token,
 }
 #[derive(Debug, Clone)]
+pub struct CharExpr {
+    pub value: char,
+    pub token: Token,
+}
+#[derive(Debug, Clone)]
 pub struct StringExpr {
     pub string: String,
     pub token: Token,
 }
 
+#[derive(Debug, Clone)]
+pub enum InterpolatedStringPart {
+    Literal(String),
+    Expr(ExprId),
+}
+
+#[derive(Debug, Clone)]
+pub struct InterpolatedStringExpr {
+    pub parts: Vec<InterpolatedStringPart>,
+    pub token: Token,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParenthesizedExpr {
     pub left_paren: Token,
@@ -596,6 +931,41 @@ impl Expr {
                 TextSpan::combine(spans)
             }
 
+            ExprKind::Loop(expr) => {
+                let mut spans = vec![expr.loop_keyword.span.clone(), ast.query_expr(expr.body).span(ast)];
+                if let Some(label) = &expr.label {
+                    spans.push(label.label.span.clone());
+                }
+                TextSpan::combine(spans)
+            }
+
+            ExprKind::Cast(expr) => {
+                let spans = vec![ast.query_expr(expr.expr).span(ast), expr.as_keyword.span.clone(), expr.type_name.span.clone()];
+                TextSpan::combine(spans)
+            }
+
+            ExprKind::Index(expr) => {
+                let spans = vec![
+                    ast.query_expr(expr.target).span(ast),
+                    expr.left_bracket.span.clone(),
+                    ast.query_expr(expr.index).span(ast),
+                    expr.right_bracket.span.clone(),
+                ];
+                TextSpan::combine(spans)
+            }
+
+            ExprKind::Slice(expr) => {
+                let spans = vec![
+                    ast.query_expr(expr.target).span(ast),
+                    expr.left_bracket.span.clone(),
+                    ast.query_expr(expr.start).span(ast),
+                    expr.range_operator.span.clone(),
+                    ast.query_expr(expr.end).span(ast),
+                    expr.right_bracket.span.clone(),
+                ];
+                TextSpan::combine(spans)
+            }
+
             ExprKind::Number(expr) => expr.token.span.clone(),
             ExprKind::Binary(expr) => {
                 let left = ast.query_expr(expr.left).span(ast);
@@ -604,7 +974,9 @@ impl Expr {
                 TextSpan::combine(vec![left, operator, right])
             }
             ExprKind::Decimal(expr) => expr.token.span.clone(),
+            ExprKind::Char(expr) => expr.token.span.clone(),
             ExprKind::String(expr) => expr.token.span.clone(),
+            ExprKind::InterpolatedString(expr) => expr.token.span.clone(),
             ExprKind::Unary(expr) => {
                 let operator = expr.operator.token.span.clone();
                 let operand = ast.query_expr(expr.operand).span(ast);
@@ -655,7 +1027,7 @@ impl Expr {
 
 #[cfg(test)]
 mod test {
-    use crate::ast::{AssignExpr, Ast, BinaryExpr, BlockExpr, BoolExpr, CallExpr, Expr, FunctionDeclaration, IfExpr, LetStmt, NumberExpr,StringExpr,DecimalExpr, ParenthesizedExpr, ReturnStmt, Stmt, UnaryExpr, VarExpr, WhileStmt};
+    use crate::ast::{AssignExpr, Ast, BinaryExpr, BlockExpr, BoolExpr, BreakStmt, CallExpr, CastExpr, CharExpr, ContinueStmt, Expr, ForStmt, FunctionDeclaration, IfExpr, IndexExpr, InterpolatedStringExpr, InterpolatedStringPart, LetStmt, LoopExpr, NumberExpr,StringExpr,DecimalExpr, ParenthesizedExpr, ReturnStmt, SliceExpr, Stmt, UnaryExpr, VarExpr, WhileStmt};
     use crate::compilation_unit::CompilationUnit;
     use crate::text::span::TextSpan;
 
@@ -663,21 +1035,36 @@ mod test {
 
     #[derive(Debug)]
     enum TestASTNode {
-        Number(i64),
+        Number(u64),
         Decimal(f64),
+        Char(char),
         String(String),
+        InterpolatedString,
         Boolean(bool),
         Binary,
         Unary,
         Parenthesized,
         Let,
         Assignment,
+        CompoundAssignment(String),
         Block,
         Variable(String),
         If,
         Else,
         Func,
         While,
+        // Whether the range includes its end
+        For(bool),
+        // A for loop over the characters of a string
+        ForEach,
+        Loop,
+        Cast,
+        Index,
+        // Whether the range includes its end
+        Slice(bool),
+        // The label the jump targets, if any
+        Break(Option<String>),
+        Continue(Option<String>),
         Return,
         Call,
     }
@@ -698,9 +1085,15 @@ mod test {
                     let diff = (n1 - n2).abs();
                     diff < 0.0000000000001 //floating point precision 
                 },
+                (TestASTNode::Char(c1), TestASTNode::Char(c2)) => c1 == c2,
                 (TestASTNode::String(s1), TestASTNode::String(s2)) => s1 == s2,
                 (TestASTNode::Boolean(b1), TestASTNode::Boolean(b2)) => b1 == b2,
-                _ => false,
+                (TestASTNode::Variable(v1), TestASTNode::Variable(v2)) => v1 == v2,
+                (TestASTNode::CompoundAssignment(o1), TestASTNode::CompoundAssignment(o2)) => o1 == o2,
+                (TestASTNode::For(i1), TestASTNode::For(i2)) => i1 == i2,
+                (TestASTNode::Break(l1), TestASTNode::Break(l2)) => l1 == l2,
+                (TestASTNode::Continue(l1), TestASTNode::Continue(l2)) => l1 == l2,
+                _ => std::mem::discriminant(self) == std::mem::discriminant(other),
             }
         }
     }
@@ -745,6 +1138,55 @@ mod test {
             self.visit_expression(ast, while_statement.body);
         }
 
+        fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, _stmt: &Stmt) {
+            match &for_statement.range {
+                Some(_) => self.actual.push(TestASTNode::For(for_statement.is_inclusive())),
+                None => self.actual.push(TestASTNode::ForEach),
+            }
+            self.visit_expression(ast, for_statement.start);
+            if let Some(range) = &for_statement.range {
+                self.visit_expression(ast, range.end);
+                if let Some(step) = &range.step {
+                    self.visit_expression(ast, step.expr);
+                }
+            }
+            self.visit_expression(ast, for_statement.body);
+        }
+
+        fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::Loop);
+            self.visit_expression(ast, loop_expr.body);
+        }
+
+        fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::Cast);
+            self.visit_expression(ast, cast_expr.expr);
+        }
+
+        fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::Index);
+            self.visit_expression(ast, index_expr.target);
+            self.visit_expression(ast, index_expr.index);
+        }
+
+        fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::Slice(slice_expr.is_inclusive()));
+            self.visit_expression(ast, slice_expr.target);
+            self.visit_expression(ast, slice_expr.start);
+            self.visit_expression(ast, slice_expr.end);
+        }
+
+        fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+            self.actual.push(TestASTNode::Break(break_statement.label.as_ref().map(|label| label.span.literal.clone())));
+            if let Some(value) = &break_statement.value {
+                self.visit_expression(ast, *value);
+            }
+        }
+
+        fn visit_continue_statement(&mut self, _ast: &mut Ast, continue_statement: &ContinueStmt) {
+            self.actual.push(TestASTNode::Continue(continue_statement.label.as_ref().map(|label| label.span.literal.clone())));
+        }
+
         fn visit_block_expr(&mut self, ast: &mut Ast, block_statement: &BlockExpr, _expr: &Expr) {
             self.actual.push(TestASTNode::Block);
             for statement in &block_statement.stmts {
@@ -776,7 +1218,10 @@ mod test {
         }
 
         fn visit_assignment_expression(&mut self, ast: &mut Ast, assignment_expression: &AssignExpr, _expr: &Expr) {
-            self.actual.push(TestASTNode::Assignment);
+            match &assignment_expression.operator {
+                Some(operator) => self.actual.push(TestASTNode::CompoundAssignment(operator.token.span.literal.clone())),
+                None => self.actual.push(TestASTNode::Assignment),
+            }
             self.visit_expression(ast, assignment_expression.expression);
         }
 
@@ -794,10 +1239,28 @@ mod test {
             self.actual.push(TestASTNode::Decimal(decimal.number));
         }
 
+        fn visit_char_expression(&mut self, _ast: &mut Ast, character: &CharExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::Char(character.value));
+        }
+
         fn visit_string_expression(&mut self, ast: &mut Ast, string: &StringExpr, _expr: &Expr) {
             self.actual.push(TestASTNode::String(string.string.clone()));
         }
 
+        fn visit_interpolated_string_expression(&mut self, ast: &mut Ast, interpolated_string: &InterpolatedStringExpr, _expr: &Expr) {
+            self.actual.push(TestASTNode::InterpolatedString);
+            for part in &interpolated_string.parts {
+                match part {
+                    InterpolatedStringPart::Literal(value) => {
+                        self.actual.push(TestASTNode::String(value.clone()));
+                    }
+                    InterpolatedStringPart::Expr(expr) => {
+                        self.visit_expression(ast, *expr);
+                    }
+                }
+            }
+        }
+
         fn visit_boolean_expression(&mut self, _ast: &mut Ast, boolean: &BoolExpr, _expr: &Expr) {
             self.actual.push(TestASTNode::Boolean(boolean.value));
         }
@@ -822,8 +1285,9 @@ mod test {
             self.visit_expression(ast, parenthesized_expression.expression);
         }
 
-        fn visit_func_decl(&mut self, _ast: &mut Ast, _func_decl: &FunctionDeclaration, _item_id: super::ItemId) {
-            todo!()
+        fn visit_func_decl(&mut self, ast: &mut Ast, func_decl: &FunctionDeclaration, _item_id: super::ItemId) {
+            self.actual.push(TestASTNode::Func);
+            self.visit_expression(ast, func_decl.body);
         }
 
         fn visit_item(&mut self, ast: &mut Ast, item: super::ItemId) {
@@ -839,6 +1303,9 @@ mod test {
                 super::ItemKind::Function(func_decl) => {
                     self.visit_func_decl(ast, func_decl, item.id);
                 }
+                super::ItemKind::Error(span) => {
+                    self.visit_error(ast, span);
+                }
             }
         }
 
@@ -854,9 +1321,21 @@ mod test {
                 super::StmtKind::While(stmt) => {
                     self.visit_while_statement(ast, &stmt);
                 }
+                super::StmtKind::For(stmt) => {
+                    self.visit_for_statement(ast, stmt, &statement);
+                }
+                super::StmtKind::Break(stmt) => {
+                    self.visit_break_statement(ast, stmt);
+                }
+                super::StmtKind::Continue(stmt) => {
+                    self.visit_continue_statement(ast, stmt);
+                }
                 super::StmtKind::Return(stmt) => {
                     self.visit_return_statement(ast, &stmt);
                 }
+                super::StmtKind::Error(span) => {
+                    self.visit_error(ast, span);
+                }
             }
         }
 
@@ -873,9 +1352,15 @@ mod test {
                 super::ExprKind::Decimal(decimal) => {
                     self.visit_decimal_expression(ast, decimal, &expression);
                 }
+                super::ExprKind::Char(character) => {
+                    self.visit_char_expression(ast, character, &expression);
+                }
                 super::ExprKind::String(string) => {
                     self.visit_string_expression(ast, string, &expression);
                 }
+                super::ExprKind::InterpolatedString(interpolated_string) => {
+                    self.visit_interpolated_string_expression(ast, interpolated_string, &expression);
+                }
                 super::ExprKind::Binary(expr) => {
                     self.visit_binary_expression(ast, expr, &expression);
                 }
@@ -906,6 +1391,18 @@ mod test {
                 super::ExprKind::Block(block_expr) => {
                     self.visit_block_expr(ast, &block_expr, &expression);
                 }
+                super::ExprKind::Loop(loop_expr) => {
+                    self.visit_loop_expression(ast, loop_expr, &expression);
+                }
+                super::ExprKind::Cast(cast_expr) => {
+                    self.visit_cast_expression(ast, cast_expr, &expression);
+                }
+                super::ExprKind::Index(index_expr) => {
+                    self.visit_index_expression(ast, index_expr, &expression);
+                }
+                super::ExprKind::Slice(slice_expr) => {
+                    self.visit_slice_expression(ast, slice_expr, &expression);
+                }
             }
         }
 
@@ -1214,4 +1711,398 @@ mod test {
 
         assert_tree(input, expected);
     }
+
+    #[test]
+    pub fn should_ignore_line_and_block_comments() {
+        let input = "\
+        // a line comment
+        let a = 1 /* inline */ + 2 // trailing
+        /* a block comment /* with a nested one */
+           spanning lines */
+        let b = a / 2
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Number(2),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_decode_string_escape_sequences() {
+        let input = r#"let a = "tab\there\nquote\" slash\\ smile\u{1F600}""#;
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::String("tab\there\nquote\" slash\\ smile\u{1F600}".to_string()),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_interpolated_string() {
+        let input = r#"
+        let name = "world"
+        let a = "hello ${name}, ${1 + 2}!"
+        "#;
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::String("world".to_string()),
+            TestASTNode::Let,
+            TestASTNode::InterpolatedString,
+            TestASTNode::String("hello ".to_string()),
+            TestASTNode::Variable("name".to_string()),
+            TestASTNode::String(", ".to_string()),
+            TestASTNode::Binary,
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::String("!".to_string()),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_strip_indentation_from_indented_string() {
+        let input = "\
+        let query = ''
+            SELECT *
+              FROM users
+            WHERE name = '''${1}''' AND path = 'C:\\dir' ''$
+        ''
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::InterpolatedString,
+            TestASTNode::String("SELECT *\n  FROM users\nWHERE name = ''".to_string()),
+            TestASTNode::Number(1),
+            TestASTNode::String("'' AND path = 'C:\\dir' $\n".to_string()),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_prefixed_and_separated_number_literals() {
+        let input = "\
+        let a = 0xFF + 0b1010_1010 + 0o17 + 1_000_000
+        let b = 1.5e3 + 25e-2
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Number(255),
+            TestASTNode::Number(170),
+            TestASTNode::Number(15),
+            TestASTNode::Number(1_000_000),
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Decimal(1500.0),
+            TestASTNode::Decimal(0.25),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_float_literals_without_prefix() {
+        let input = "\
+        let data = 56.2
+        let delta = .5 + 1e-3
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Decimal(56.2),
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Decimal(0.5),
+            TestASTNode::Decimal(0.001),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_identifiers_with_underscores_digits_and_unicode() {
+        let input = "\
+        let my_var = 1
+        let _tmp2 = my_var
+        let größe = _tmp2
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Number(1),
+            TestASTNode::Let,
+            TestASTNode::Variable("my_var".to_string()),
+            TestASTNode::Let,
+            TestASTNode::Variable("_tmp2".to_string()),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_logical_operators_below_bitwise_operators() {
+        let input = "let a = !true && false || (1 | 2) == 3";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Unary,
+            TestASTNode::Boolean(true),
+            TestASTNode::Boolean(false),
+            TestASTNode::Binary,
+            TestASTNode::Parenthesized,
+            TestASTNode::Binary,
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::Number(3),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_comparisons_below_arithmetic_and_bitwise_operators() {
+        let input = "let a = 1 + 2 == 3 & 3";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::Binary,
+            TestASTNode::Number(3),
+            TestASTNode::Number(3),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_shifts_between_additive_and_bitwise_operators() {
+        let input = "let a = 1 + 2 << 3 % 2 & 4";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::Binary,
+            TestASTNode::Number(3),
+            TestASTNode::Number(2),
+            TestASTNode::Number(4),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_compound_assignments() {
+        let input = "\
+        let a = 1
+        a += 2
+        a >>>= a - 1
+        a **= 3
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Number(1),
+            TestASTNode::CompoundAssignment("+=".to_string()),
+            TestASTNode::Number(2),
+            TestASTNode::CompoundAssignment(">>>=".to_string()),
+            TestASTNode::Binary,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Number(1),
+            TestASTNode::CompoundAssignment("**=".to_string()),
+            TestASTNode::Number(3),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_for_loops_over_ranges() {
+        let input = "\
+        let n = 3
+        for i in 0..n {
+            i
+        }
+        for step in 1..=10 step 2 {}
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Number(3),
+            TestASTNode::For(false),
+            TestASTNode::Number(0),
+            TestASTNode::Variable("n".to_string()),
+            TestASTNode::Block,
+            TestASTNode::Variable("i".to_string()),
+            TestASTNode::For(true),
+            TestASTNode::Number(1),
+            TestASTNode::Number(10),
+            TestASTNode::Number(2),
+            TestASTNode::Block,
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_labeled_loops_with_break_and_continue() {
+        let input = "\
+        let a = 'outer: loop {
+            for i in 0..10 {
+                continue 'outer
+            }
+            break 'outer 1
+        }
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Loop,
+            TestASTNode::Block,
+            TestASTNode::For(false),
+            TestASTNode::Number(0),
+            TestASTNode::Number(10),
+            TestASTNode::Block,
+            TestASTNode::Continue(Some("'outer".to_string())),
+            TestASTNode::Break(Some("'outer".to_string())),
+            TestASTNode::Number(1),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_not_take_the_next_line_as_break_value() {
+        let input = "\
+        let a = 0
+        while true {
+            break
+            a
+        }
+        ";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Number(0),
+            TestASTNode::While,
+            TestASTNode::Boolean(true),
+            TestASTNode::Block,
+            TestASTNode::Break(None),
+            TestASTNode::Variable("a".to_string()),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_empty_return_statements() {
+        let input = "\
+        func f(a: int) {
+            if a > 0 {
+                return;
+            }
+            if a < 0 {
+                return
+            }
+            f(1)
+        }
+        ";
+        let expected = vec![
+            TestASTNode::Func,
+            TestASTNode::Block,
+            TestASTNode::If,
+            TestASTNode::Binary,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Number(0),
+            TestASTNode::Block,
+            TestASTNode::Return,
+            TestASTNode::If,
+            TestASTNode::Binary,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Number(0),
+            TestASTNode::Block,
+            TestASTNode::Return,
+            TestASTNode::Call,
+            TestASTNode::Number(1),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_casts_between_prefix_and_binary_operators() {
+        let input = "let a = -1 as float * 2.5 as int as float";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Cast,
+            TestASTNode::Unary,
+            TestASTNode::Number(1),
+            TestASTNode::Cast,
+            TestASTNode::Cast,
+            TestASTNode::Decimal(2.5),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_index_and_slice_expressions() {
+        let input = "let i = 0\nlet a = -len(\"text\"[1..=2]) + len(\"text\"[1..4][i..3]) + \"text\"[0] as int";
+        let expected = vec![
+            TestASTNode::Let,
+            TestASTNode::Number(0),
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Binary,
+            TestASTNode::Unary,
+            TestASTNode::Call,
+            TestASTNode::Slice(true),
+            TestASTNode::String("text".to_string()),
+            TestASTNode::Number(1),
+            TestASTNode::Number(2),
+            TestASTNode::Call,
+            TestASTNode::Slice(false),
+            TestASTNode::Slice(false),
+            TestASTNode::String("text".to_string()),
+            TestASTNode::Number(1),
+            TestASTNode::Number(4),
+            TestASTNode::Variable("i".to_string()),
+            TestASTNode::Number(3),
+            TestASTNode::Cast,
+            TestASTNode::Index,
+            TestASTNode::String("text".to_string()),
+            TestASTNode::Number(0),
+        ];
+
+        assert_tree(input, expected);
+    }
+
+    #[test]
+    pub fn should_parse_char_literals_and_for_loops_over_strings() {
+        let input = "for c in \"abc\" {\n    let b = c < 'z'\n}";
+        let expected = vec![
+            TestASTNode::ForEach,
+            TestASTNode::String("abc".to_string()),
+            TestASTNode::Block,
+            TestASTNode::Let,
+            TestASTNode::Binary,
+            TestASTNode::Variable("c".to_string()),
+            TestASTNode::Char('z'),
+        ];
+
+        assert_tree(input, expected);
+    }
 }
@@ -1,8 +1,9 @@
 use std::cell::Cell;
-use crate::ast::{Ast, BinOpAssociativity, BinOperator, BinOpKind, ElseBranch, Expr, ExprId, FuncDeclParameter, FunctionReturnTypeSyntax, Item, ItemKind, StaticTypeAnnotation, Stmt, StmtId, UnOperator, UnOpKind};
-use crate::ast::lexer::{Token, TokenKind};
-use crate::compilation_unit::{GlobalScope, resolve_type_from_string};
-use crate::diagnostics::DiagnosticsBagCell;
+use std::rc::Rc;
+use crate::ast::{Ast, BinOpAssociativity, BinOperator, BinOpKind, ElseBranch, Expr, ExprId, ExprKind, ForRange, ForStep, FuncDeclParameter, FunctionReturnTypeSyntax, InterpolatedStringPart, Item, ItemKind, LoopLabel, StaticTypeAnnotation, Stmt, StmtId, UnOperator, UnOpKind};
+use crate::ast::lexer::{StringFragment, Token, TokenKind};
+use crate::diagnostics::{DiagnosticsBag, DiagnosticsBagCell};
+use crate::text::span::TextSpan;
 use crate::typings::Type;
 
 #[derive(Debug, Clone)]
@@ -29,10 +30,13 @@ impl Counter {
 
 pub struct Parser<'a> {
     tokens: Vec<Token>,
+    // Whether a line break separates each token from the previous one
+    starts_line: Vec<bool>,
     current: Counter,
+    // Set after a syntax error until the parser resynchronizes, errors are not reported in the meantime
+    panicking: Cell<bool>,
     diagnostics_bag: DiagnosticsBagCell,
     ast: &'a mut Ast,
-    global_scope: &'a mut GlobalScope,
 }
 
 impl<'a> Parser<'a> {
@@ -40,16 +44,26 @@ impl<'a> Parser<'a> {
         tokens: Vec<Token>,
         diagnostics_bag: DiagnosticsBagCell,
         ast: &'a mut Ast,
-        global_scope: &'a mut GlobalScope,
     ) -> Self {
+        let mut starts_line = Vec::new();
+        let mut line_break = false;
+        for token in &tokens {
+            if token.kind.is_trivia() {
+                line_break |= token.span.literal.contains('\n');
+            } else {
+                starts_line.push(line_break);
+                line_break = false;
+            }
+        }
         Self {
             tokens: tokens.iter().filter(
-                |token| token.kind != TokenKind::Whitespace
+                |token| !token.kind.is_trivia()
             ).map(|token| token.clone()).collect(),
+            starts_line,
             current: Counter::new(),
+            panicking: Cell::new(false),
             diagnostics_bag,
             ast,
-            global_scope,
         }
     }
 
@@ -65,7 +79,7 @@ impl<'a> Parser<'a> {
     }
 
     fn is_at_end(&self) -> bool {
-        self.current().kind == TokenKind::Eof
+        matches!(self.current().kind, TokenKind::Eof | TokenKind::StringEnd)
     }
 
     fn parse_item(&mut self) -> &Item {
@@ -80,49 +94,49 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_func_item(&mut self) -> &Item {
-        let func_keyword = self.consume_and_check(TokenKind::Func).clone();
-        let identifier = self.consume_and_check(TokenKind::Identifier).clone();
+        let start = self.current.get_value();
+        let func_keyword = self.consume_and_check(TokenKind::Func);
+        let identifier = self.consume_and_check(TokenKind::Identifier);
+        if identifier.is_missing() {
+            // Without a name there is nothing to declare
+            self.synchronize(start);
+            let span = self.span_from(start);
+            return self.ast.item_from_kind(ItemKind::Error(span));
+        }
         let parameters = self.parse_optional_parameter_list();
         let return_type = self.parse_optional_return_type();
 
         let body = self.parse_expr();
-        let declared_parameters = parameters
-            .iter()
-            .map(
-                |parameter| {
-                    let ty = resolve_type_from_string(&self.diagnostics_bag, &parameter.type_annotation.type_name);
-                    self.global_scope.declare_variable(
-                        &parameter.identifier.span.literal,
-                        ty,
-                        false,
-                    )
-                }
-            ).collect();
-        let created_function_idx_result = self.global_scope.create_function(
-            identifier.span.literal.clone(),
-            body,
-            declared_parameters,
-            return_type.clone().map(|return_type| resolve_type_from_string(&self.diagnostics_bag, &return_type.type_name)).unwrap_or(Type::Void),
-        );
-        let function_idx =  match created_function_idx_result {
-            Ok(created_function_idx) => created_function_idx,
-            Err(already_existing_function_idx) => {
-                self.diagnostics_bag.borrow_mut().report_function_already_declared(
-                    &identifier,
-                );
-                already_existing_function_idx
-            }
-        };
-        return self.ast.func_item(func_keyword, identifier, parameters, body, return_type, function_idx);
+        if self.panicking.get() {
+            self.synchronize(start);
+        }
+        return self.ast.func_item(func_keyword, identifier, parameters, body, return_type);
     }
 
     fn parse_statement(&mut self) -> StmtId {
+        let start = self.current.get_value();
         let stmt = match self.current().kind {
             TokenKind::Let => {
                 self.parse_let_statement().id
             }
             TokenKind::While => {
-                self.parse_while_statement().id
+                self.parse_while_statement(None).id
+            }
+            TokenKind::For => {
+                self.parse_for_statement(None).id
+            }
+            TokenKind::Label if matches!(self.peek(2).kind, TokenKind::While | TokenKind::For) => {
+                let label = self.parse_loop_label();
+                match self.current().kind {
+                    TokenKind::While => self.parse_while_statement(Some(label)).id,
+                    _ => self.parse_for_statement(Some(label)).id,
+                }
+            }
+            TokenKind::Break => {
+                self.parse_break_statement().id
+            }
+            TokenKind::Continue => {
+                self.parse_continue_statement().id
             }
             TokenKind::Return => {
                 self.parse_return_statement().id
@@ -132,13 +146,49 @@ impl<'a> Parser<'a> {
             }
         };
         self.consume_if(TokenKind::SemiColon);
+        if self.panicking.get() {
+            self.synchronize(start);
+        }
         stmt
     }
 
+    /// Skips tokens until the parser reaches a point from which it can continue
+    /// parsing: after a `;`, or before a `}`, `func` or `let`. Blocks are skipped as a
+    /// whole. At least one token is skipped if nothing was consumed since `start`, so
+    /// that the parser cannot get stuck on a token it can't parse.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = 0;
+        loop {
+            match self.current().kind {
+                TokenKind::Eof | TokenKind::StringEnd => break,
+                TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseBrace if depth > 0 => depth -= 1,
+                TokenKind::SemiColon if depth == 0 => {
+                    self.consume();
+                    break;
+                }
+                TokenKind::CloseBrace | TokenKind::Func | TokenKind::Let if depth == 0 => break,
+                _ => {}
+            }
+            self.consume();
+        }
+        if self.current.get_value() == start && !self.is_at_end() {
+            self.consume();
+        }
+        self.panicking.set(false);
+    }
+
+    /// The span from the token at index `start` to the last consumed token.
+    fn span_from(&self, start: usize) -> TextSpan {
+        let end = self.current.get_value().max(start + 1).min(self.tokens.len());
+        let spans = self.tokens[start.min(end - 1)..end].iter().map(|token| token.span.clone()).collect();
+        TextSpan::combine(spans)
+    }
+
     fn parse_optional_return_type(&mut self) -> Option<FunctionReturnTypeSyntax> {
         if self.current().kind == TokenKind::Arrow {
-            let arrow = self.consume_and_check(TokenKind::Arrow).clone();
-            let type_name = self.consume_and_check(TokenKind::Identifier).clone();
+            let arrow = self.consume_and_check(TokenKind::Arrow);
+            let type_name = self.consume_and_check(TokenKind::Identifier);
             return Some(FunctionReturnTypeSyntax::new(
                 arrow,
                 type_name,
@@ -153,9 +203,9 @@ impl<'a> Parser<'a> {
         }
         self.consume_and_check(TokenKind::LeftParen);
         let mut parameters = Vec::new();
-        while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+        while self.current().kind != TokenKind::RightParen && !self.is_at_end() && !self.panicking.get() {
             parameters.push(FuncDeclParameter {
-                identifier: self.consume_and_check(TokenKind::Identifier).clone(),
+                identifier: self.consume_and_check(TokenKind::Identifier),
                 type_annotation: self.parse_type_annotation(),
 
             });
@@ -168,25 +218,91 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_return_statement(&mut self) -> &Stmt {
-        let return_keyword = self.consume_and_check(TokenKind::Return).clone();
-        // todo: allow empty return statements
-        let expression = self.parse_expr();
-        self.ast.return_statement(return_keyword, Some(expression))
+        let return_keyword = self.consume_and_check(TokenKind::Return);
+        let expression = if self.continues_statement() {
+            Some(self.parse_expr())
+        } else {
+            None
+        };
+        self.ast.return_statement(return_keyword, expression)
     }
 
-    fn parse_while_statement(&mut self) -> &Stmt {
-        let while_keyword = self.consume_and_check(TokenKind::While).clone();
+    fn parse_while_statement(&mut self, label: Option<LoopLabel>) -> &Stmt {
+        let while_keyword = self.consume_and_check(TokenKind::While);
         let condition_expr = self.parse_expr();
         let body = self.parse_expr();
-        self.ast.while_statement(while_keyword, condition_expr, body)
+        self.ast.while_statement(label, while_keyword, condition_expr, body)
+    }
+
+    fn parse_loop_label(&mut self) -> LoopLabel {
+        let label = self.consume_and_check(TokenKind::Label);
+        let colon = self.consume_and_check(TokenKind::Colon);
+        LoopLabel { label, colon }
+    }
+
+    fn parse_break_statement(&mut self) -> &Stmt {
+        let break_keyword = self.consume_and_check(TokenKind::Break);
+        let label = self.parse_optional_label_reference();
+        let value = if self.continues_statement() {
+            Some(self.parse_expr())
+        } else {
+            None
+        };
+        self.ast.break_statement(break_keyword, label, value)
+    }
+
+    fn parse_continue_statement(&mut self) -> &Stmt {
+        let continue_keyword = self.consume_and_check(TokenKind::Continue);
+        let label = self.parse_optional_label_reference();
+        self.ast.continue_statement(continue_keyword, label)
+    }
+
+    /// The `'label` after `break` or `continue`.
+    fn parse_optional_label_reference(&mut self) -> Option<Token> {
+        if self.current().kind == TokenKind::Label && self.continues_statement() {
+            return Some(self.consume().clone());
+        }
+        None
+    }
+
+    /// Whether the current token continues the statement before it rather than starting
+    /// the next one. Statements don't need a `;`, so a line break ends them.
+    fn continues_statement(&self) -> bool {
+        !self.starts_line[self.current.get_value().min(self.starts_line.len() - 1)]
+            && !Self::ends_expression(&self.current().kind)
+    }
+
+    fn parse_for_statement(&mut self, label: Option<LoopLabel>) -> &Stmt {
+        let for_keyword = self.consume_and_check(TokenKind::For);
+        let identifier = self.consume_and_check(TokenKind::Identifier);
+        let in_keyword = self.consume_and_check(TokenKind::In);
+        let start = self.parse_expr();
+        let range = match self.current().kind {
+            TokenKind::DotDot | TokenKind::DotDotEquals => {
+                let range_operator = self.consume().clone();
+                let end = self.parse_expr();
+                let step = if self.current().kind == TokenKind::Identifier && self.current().span.literal == "step" {
+                    let step_keyword = self.consume().clone();
+                    Some(ForStep { step_keyword, expr: self.parse_expr() })
+                } else {
+                    None
+                };
+                Some(ForRange { range_operator, end, step })
+            }
+            _ => None,
+        };
+        let body = self.parse_expr();
+        self.ast.for_statement(label, for_keyword, identifier, in_keyword, start, range, body)
     }
 
     fn parse_block_expression(&mut self, left_brace: Token) -> &Expr {
+        // The statements of a block start in a known state, whatever went wrong before the brace
+        self.panicking.set(false);
         let mut statements = Vec::new();
         while self.current().kind != TokenKind::CloseBrace && !self.is_at_end() {
             statements.push(self.parse_statement());
         }
-        let right_brace = self.consume_and_check(TokenKind::CloseBrace).clone();
+        let right_brace = self.consume_and_check(TokenKind::CloseBrace);
         self.ast.block_expression(left_brace, statements, right_brace)
     }
 
@@ -199,7 +315,7 @@ impl<'a> Parser<'a> {
 
     fn parse_optional_else_statement(&mut self) -> Option<ElseBranch> {
         if self.current().kind == TokenKind::Else {
-            let else_keyword = self.consume_and_check(TokenKind::Else).clone();
+            let else_keyword = self.consume_and_check(TokenKind::Else);
             let else_expr = self.parse_expr();
             return Some(ElseBranch::new(else_keyword, else_expr));
         }
@@ -208,8 +324,15 @@ impl<'a> Parser<'a> {
 
 
     fn parse_let_statement(&mut self) -> &Stmt {
+        let start = self.current.get_value();
         self.consume_and_check(TokenKind::Let);
-        let identifier = self.consume_and_check(TokenKind::Identifier).clone();
+        let identifier = self.consume_and_check(TokenKind::Identifier);
+        if identifier.is_missing() {
+            // Without a name there is nothing to declare
+            self.synchronize(start);
+            let span = self.span_from(start);
+            return self.ast.error_statement(span);
+        }
         let optional_type_annotation = self.parse_optional_type_annotation();
         self.consume_and_check(TokenKind::Equals);
         let expr = self.parse_expr();
@@ -225,8 +348,8 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_type_annotation(&mut self) -> StaticTypeAnnotation {
-        let colon = self.consume_and_check(TokenKind::Colon).clone();
-        let type_name = self.consume_and_check(TokenKind::Identifier).clone();
+        let colon = self.consume_and_check(TokenKind::Colon);
+        let type_name = self.consume_and_check(TokenKind::Identifier);
         return StaticTypeAnnotation::new(colon, type_name);
     }
 
@@ -242,15 +365,41 @@ impl<'a> Parser<'a> {
     fn parse_assignment_expression(&mut self) -> ExprId {
         if self.current().kind == TokenKind::Identifier {
             if self.peek(1).kind == TokenKind::Equals {
-                let identifier = self.consume_and_check(TokenKind::Identifier).clone();
-                let equals = self.consume_and_check(TokenKind::Equals).clone();
+                let identifier = self.consume_and_check(TokenKind::Identifier);
+                let equals = self.consume_and_check(TokenKind::Equals);
                 let expr = self.parse_expr();
-                return self.ast.assignment_expression(identifier, equals, expr).id;
+                return self.ast.assignment_expression(identifier, equals, None, expr).id;
+            }
+            if let Some(kind) = Self::compound_assignment_operator(&self.peek(1).kind) {
+                let identifier = self.consume_and_check(TokenKind::Identifier);
+                let equals = self.consume().clone();
+                let operator = BinOperator::new(kind, equals.clone());
+                let expr = self.parse_expr();
+                return self.ast.assignment_expression(identifier, equals, Some(operator), expr).id;
             }
         }
         return self.parse_binary_expression();
     }
 
+    /// The binary operator applied by a compound assignment token such as `+=`.
+    fn compound_assignment_operator(kind: &TokenKind) -> Option<BinOpKind> {
+        match kind {
+            TokenKind::PlusEquals => Some(BinOpKind::Plus),
+            TokenKind::MinusEquals => Some(BinOpKind::Minus),
+            TokenKind::AsteriskEquals => Some(BinOpKind::Multiply),
+            TokenKind::SlashEquals => Some(BinOpKind::Divide),
+            TokenKind::PercentEquals => Some(BinOpKind::Modulo),
+            TokenKind::DoubleAsteriskEquals => Some(BinOpKind::Power),
+            TokenKind::AmpersandEquals => Some(BinOpKind::BitwiseAnd),
+            TokenKind::PipeEquals => Some(BinOpKind::BitwiseOr),
+            TokenKind::CaretEquals => Some(BinOpKind::BitwiseXor),
+            TokenKind::LessThanLessThanEquals => Some(BinOpKind::ShiftLeft),
+            TokenKind::GreaterThanGreaterThanEquals => Some(BinOpKind::ShiftRight),
+            TokenKind::GreaterThanGreaterThanGreaterThanEquals => Some(BinOpKind::UnsignedShiftRight),
+            _ => None,
+        }
+    }
+
     fn parse_binary_expression(&mut self) -> ExprId {
         let left = self.parse_unary_expression();
         self.parse_binary_expression_recurse(left, 0)
@@ -274,18 +423,65 @@ impl<'a> Parser<'a> {
 
                 right = self.parse_binary_expression_recurse(right, std::cmp::max(operator.precedence(), inner_operator.precedence()));
             }
+            if operator.associativity() == BinOpAssociativity::None && (self.is_comparison(left) || self.is_comparison(right)) {
+                self.diagnostics_bag.borrow_mut().report_chained_comparison(&operator.token.span);
+                let span = TextSpan::combine(vec![self.ast.query_expr(left).span(self.ast), self.ast.query_expr(right).span(self.ast)]);
+                left = self.ast.error_expression(span).id;
+                continue;
+            }
             left = self.ast.binary_expression(operator, left, right).id;
         }
         left
     }
 
+    /// Whether `expr` is an unparenthesized comparison, used to reject `a < b < c`.
+    fn is_comparison(&self, expr: ExprId) -> bool {
+        match &self.ast.query_expr(expr).kind {
+            ExprKind::Binary(binary_expr) => binary_expr.operator.associativity() == BinOpAssociativity::None,
+            _ => false,
+        }
+    }
+
+    /// Casts bind looser than prefix operators, `-a as float` casts `-a`.
     fn parse_unary_expression(&mut self) -> ExprId {
+        let mut expr = self.parse_prefix_expression();
+        while self.current().kind == TokenKind::As {
+            let as_keyword = self.consume().clone();
+            let type_name = self.consume_and_check(TokenKind::Identifier);
+            expr = self.ast.cast_expression(expr, as_keyword, type_name).id;
+        }
+        expr
+    }
+
+    fn parse_prefix_expression(&mut self) -> ExprId {
         if let Some(operator) = self.parse_unary_operator() {
             self.consume();
-            let operand = self.parse_unary_expression();
+            let operand = self.parse_prefix_expression();
             return self.ast.unary_expression(operator, operand).id;
         }
-        return self.parse_primary_expression();
+        return self.parse_postfix_expression();
+    }
+
+    /// Indexing binds tighter than prefix operators, `-s[0]` negates `s[0]`.
+    fn parse_postfix_expression(&mut self) -> ExprId {
+        let mut expr = self.parse_primary_expression();
+        while self.current().kind == TokenKind::LeftBracket {
+            let left_bracket = self.consume().clone();
+            let start = self.parse_expr();
+            expr = match self.current().kind {
+                TokenKind::DotDot | TokenKind::DotDotEquals => {
+                    let range_operator = self.consume().clone();
+                    let end = self.parse_expr();
+                    let right_bracket = self.consume_and_check(TokenKind::RightBracket);
+                    self.ast.slice_expression(expr, left_bracket, start, range_operator, end, right_bracket).id
+                }
+                _ => {
+                    let right_bracket = self.consume_and_check(TokenKind::RightBracket);
+                    self.ast.index_expression(expr, left_bracket, start, right_bracket).id
+                }
+            };
+        }
+        expr
     }
 
     fn parse_unary_operator(&mut self) -> Option<UnOperator> {
@@ -297,6 +493,9 @@ impl<'a> Parser<'a> {
             TokenKind::Tilde => {
                 Some(UnOpKind::BitwiseNot)
             }
+            TokenKind::Bang => {
+                Some(UnOpKind::LogicalNot)
+            }
             _ => {
                 None
             }
@@ -319,6 +518,18 @@ impl<'a> Parser<'a> {
             TokenKind::Slash => {
                 Some(BinOpKind::Divide)
             }
+            TokenKind::Percent => {
+                Some(BinOpKind::Modulo)
+            }
+            TokenKind::LessThanLessThan => {
+                Some(BinOpKind::ShiftLeft)
+            }
+            TokenKind::GreaterThanGreaterThan => {
+                Some(BinOpKind::ShiftRight)
+            }
+            TokenKind::GreaterThanGreaterThanGreaterThan => {
+                Some(BinOpKind::UnsignedShiftRight)
+            }
             TokenKind::Ampersand => {
                 Some(BinOpKind::BitwiseAnd)
             }
@@ -349,6 +560,12 @@ impl<'a> Parser<'a> {
             TokenKind::GreaterThanEquals => {
                 Some(BinOpKind::GreaterThanOrEqual)
             }
+            TokenKind::AmpersandAmpersand => {
+                Some(BinOpKind::LogicalAnd)
+            }
+            TokenKind::PipePipe => {
+                Some(BinOpKind::LogicalOr)
+            }
 
             _ => {
                 None
@@ -358,6 +575,13 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_primary_expression(&mut self) -> ExprId {
+        if Self::ends_expression(&self.current().kind) {
+            // Leave closing delimiters and synchronization points to the enclosing construct
+            let token = self.current().clone();
+            self.report(|diagnostics_bag| diagnostics_bag.report_expected_expression(&token));
+            let span = TextSpan::new(token.span.start, token.span.start, String::new());
+            return self.ast.error_expression(span).id;
+        }
         let token = self.consume().clone();
         return match token.kind {
             TokenKind::OpenBrace => {
@@ -366,11 +590,26 @@ impl<'a> Parser<'a> {
             TokenKind::If => {
                 self.parse_if_expression(token)
             }
-            TokenKind::Number(number) => {
-                self.ast.number_expression(token, number)
+            TokenKind::Loop => {
+                let body = self.parse_expr();
+                self.ast.loop_expression(None, token, body)
+            }
+            TokenKind::Label => {
+                let colon = self.consume_and_check(TokenKind::Colon);
+                let label = LoopLabel { label: token, colon };
+                let loop_keyword = self.consume_and_check(TokenKind::Loop);
+                let body = self.parse_expr();
+                self.ast.loop_expression(Some(label), loop_keyword, body)
+            }
+            TokenKind::Number(number, suffix) => {
+                self.ast.number_expression(token, number, suffix)
             }
-            TokenKind::String(ref string) => {
-                self.ast.string_expression(token.clone(), string.to_string())
+            TokenKind::Char(value) => {
+                self.ast.char_expression(token, value)
+            }
+            TokenKind::String { ref fragments, .. } => {
+                let fragments = fragments.clone();
+                self.parse_string_expression(token, fragments)
             }
            
             TokenKind::Decimal(decimal) => {
@@ -379,7 +618,7 @@ impl<'a> Parser<'a> {
             TokenKind::LeftParen => {
                 let expr = self.parse_expr();
                 let left_paren = token;
-                let right_paren = self.consume_and_check(TokenKind::RightParen).clone();
+                let right_paren = self.consume_and_check(TokenKind::RightParen);
                 self.ast.parenthesized_expression(left_paren, expr, right_paren)
             }
             TokenKind::Identifier => {
@@ -393,22 +632,53 @@ impl<'a> Parser<'a> {
                 self.ast.boolean_expression(token, value)
             }
             _ => {
-                self.diagnostics_bag.borrow_mut().report_expected_expression(&token);
+                self.report(|diagnostics_bag| diagnostics_bag.report_expected_expression(&token));
                 self.ast.error_expression(token.span)
             }
         }.id;
     }
 
+    fn parse_string_expression(&mut self, token: Token, fragments: Vec<StringFragment>) -> &Expr {
+        if let [StringFragment::Literal { value }] = fragments.as_slice() {
+            return self.ast.string_expression(token.clone(), value.clone());
+        }
+        let mut parts = Vec::new();
+        for fragment in fragments {
+            match fragment {
+                StringFragment::Literal { value } => {
+                    parts.push(InterpolatedStringPart::Literal(value));
+                }
+                StringFragment::Interpolation { tokens } => {
+                    parts.push(InterpolatedStringPart::Expr(self.parse_interpolation(tokens)));
+                }
+            }
+        }
+        self.ast.interpolated_string_expression(token, parts)
+    }
+
+    /// Parses the tokens of a `${...}` block with a nested parser that shares this
+    /// parser's AST and diagnostics.
+    fn parse_interpolation(&mut self, tokens: Vec<Token>) -> ExprId {
+        let mut parser = Parser::new(
+            tokens,
+            Rc::clone(&self.diagnostics_bag),
+            self.ast,
+        );
+        let expr = parser.parse_expr();
+        parser.consume_and_check(TokenKind::StringEnd);
+        expr
+    }
+
     fn parse_call_expression(&mut self, identifier: Token) -> ExprId {
-        let left_paren = self.consume_and_check(TokenKind::LeftParen).clone();
+        let left_paren = self.consume_and_check(TokenKind::LeftParen);
         let mut arguments = Vec::new();
-        while self.current().kind != TokenKind::RightParen && !self.is_at_end() {
+        while self.current().kind != TokenKind::RightParen && !self.is_at_end() && !self.panicking.get() {
             arguments.push(self.parse_expr());
             if self.current().kind != TokenKind::RightParen {
                 self.consume_and_check(TokenKind::Comma);
             }
         }
-        let right_paren = self.consume_and_check(TokenKind::RightParen).clone();
+        let right_paren = self.consume_and_check(TokenKind::RightParen);
         return self.ast.call_expression(identifier, left_paren, arguments, right_paren).id;
     }
 
@@ -438,14 +708,31 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn consume_and_check(&self, kind: TokenKind) -> &Token {
-        let token = self.consume();
-        if token.kind != kind {
-            self.diagnostics_bag.borrow_mut().report_unexpected_token(
-                &kind,
-                token,
-            );
+    /// Consumes the current token if it is of the expected kind. Otherwise the error is
+    /// reported and a missing token of the expected kind is returned in its place,
+    /// leaving the current token for the caller to recover from.
+    fn consume_and_check(&self, kind: TokenKind) -> Token {
+        let token = self.current().clone();
+        if token.kind == kind {
+            self.consume();
+            return token;
+        }
+        self.report(|diagnostics_bag| diagnostics_bag.report_unexpected_token(&kind, &token));
+        Token::missing(kind, token.span.start)
+    }
+
+    fn ends_expression(kind: &TokenKind) -> bool {
+        matches!(
+            kind,
+            TokenKind::SemiColon | TokenKind::RightParen | TokenKind::RightBracket | TokenKind::CloseBrace | TokenKind::Func | TokenKind::Let | TokenKind::Eof | TokenKind::StringEnd
+        )
+    }
+
+    /// Reports a syntax error unless the parser is still recovering from a previous one.
+    fn report(&self, report: impl FnOnce(&mut DiagnosticsBag)) {
+        if !self.panicking.get() {
+            report(&mut self.diagnostics_bag.borrow_mut());
         }
-        token
+        self.panicking.set(true);
     }
-}
\ No newline at end of file
+}
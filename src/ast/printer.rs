@@ -43,6 +43,25 @@ impl ASTPrinter {
                                       variable, ));
     }
 
+    /// Parentheses the source didn't have are only needed for trees built by hand, the
+    /// parser already reflects the precedence table.
+    fn binary_operand_needs_parentheses(ast: &Ast, operand: ExprId, operator: &BinOpKind, side: OperandSide) -> bool {
+        match &ast.query_expr(operand).kind {
+            ExprKind::Binary(operand) => operator.operand_needs_parentheses(&operand.operator.kind, side, BinOpKind::precedence),
+            _ => false,
+        }
+    }
+
+    fn visit_operand(&mut self, ast: &mut Ast, operand: ExprId, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.add_text("(");
+        }
+        self.visit_expression(ast, operand);
+        if needs_parentheses {
+            self.add_text(")");
+        }
+    }
+
     fn add_padding(&mut self) {
         for _ in 0..self.indent {
             self.result.push_str("  ");
@@ -70,6 +89,14 @@ impl ASTPrinter {
         self.add_type(&type_annotation.type_name.span.literal);
     }
 
+    fn add_loop_label(&mut self, label: &Option<LoopLabel>) {
+        if let Some(label) = label {
+            self.add_text(label.name());
+            self.add_text(":");
+            self.add_whitespace();
+        }
+    }
+
     pub fn new() -> Self {
         Self { indent: 0, result: String::new() }
     }
@@ -109,12 +136,59 @@ impl ASTVisitor for ASTPrinter {
         }
     }
     fn visit_while_statement(&mut self, ast: &mut Ast, while_statement: &WhileStmt) {
+        self.add_loop_label(&while_statement.label);
         self.add_keyword("while");
         self.add_whitespace();
         self.visit_expression(ast, while_statement.condition);
         self.add_whitespace();
         self.visit_expression(ast, while_statement.body);
     }
+    fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, _stmt: &Stmt) {
+        self.add_loop_label(&for_statement.label);
+        self.add_keyword("for");
+        self.add_whitespace();
+        self.add_variable(&for_statement.identifier.span.literal);
+        self.add_whitespace();
+        self.add_keyword("in");
+        self.add_whitespace();
+        self.visit_expression(ast, for_statement.start);
+        if let Some(range) = &for_statement.range {
+            self.add_text(&range.range_operator.span.literal);
+            self.visit_expression(ast, range.end);
+            if let Some(step) = &range.step {
+                self.add_whitespace();
+                self.add_keyword("step");
+                self.add_whitespace();
+                self.visit_expression(ast, step.expr);
+            }
+        }
+        self.add_whitespace();
+        self.visit_expression(ast, for_statement.body);
+    }
+    fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, _expr: &Expr) {
+        self.add_loop_label(&loop_expr.label);
+        self.add_keyword("loop");
+        self.add_whitespace();
+        self.visit_expression(ast, loop_expr.body);
+    }
+    fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+        self.add_keyword("break");
+        if let Some(label) = &break_statement.label {
+            self.add_whitespace();
+            self.add_text(&label.span.literal);
+        }
+        if let Some(value) = &break_statement.value {
+            self.add_whitespace();
+            self.visit_expression(ast, *value);
+        }
+    }
+    fn visit_continue_statement(&mut self, _ast: &mut Ast, continue_statement: &ContinueStmt) {
+        self.add_keyword("continue");
+        if let Some(label) = &continue_statement.label {
+            self.add_whitespace();
+            self.add_text(&label.span.literal);
+        }
+    }
     fn visit_block_expr(&mut self, ast: &mut Ast, block_statement: &BlockExpr, _expr: &Expr) {
         self.add_text("{");
         self.add_newline();
@@ -177,10 +251,34 @@ impl ASTVisitor for ASTPrinter {
         self.add_text(")");
     }
 
+    fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, _expr: &Expr) {
+        self.visit_expression(ast, cast_expr.expr);
+        self.add_whitespace();
+        self.add_keyword("as");
+        self.add_whitespace();
+        self.add_type(&cast_expr.type_name.span.literal);
+    }
+
+    fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, _expr: &Expr) {
+        self.visit_expression(ast, index_expr.target);
+        self.add_text("[");
+        self.visit_expression(ast, index_expr.index);
+        self.add_text("]");
+    }
+
+    fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, _expr: &Expr) {
+        self.visit_expression(ast, slice_expr.target);
+        self.add_text("[");
+        self.visit_expression(ast, slice_expr.start);
+        self.add_text(&slice_expr.range_operator.span.literal);
+        self.visit_expression(ast, slice_expr.end);
+        self.add_text("]");
+    }
+
     fn visit_assignment_expression(&mut self, ast: &mut Ast, assignment_expression: &AssignExpr, _expr: &Expr) {
         self.add_variable(assignment_expression.identifier.span.literal.as_str());
         self.add_whitespace();
-        self.add_text("=");
+        self.add_text(&assignment_expression.equals.span.literal);
         self.add_whitespace();
         self.visit_expression(ast, assignment_expression.expression);
     }
@@ -192,9 +290,10 @@ impl ASTVisitor for ASTPrinter {
     }
 
     fn visit_number_expression(&mut self, _ast: &mut Ast, number: &NumberExpr, _expr: &Expr) {
-        self.result.push_str(&format!("{}{}",
+        self.result.push_str(&format!("{}{}{}",
                                       Self::NUMBER_COLOR.fg_str(),
-                                      number.number, ));
+                                      number.number,
+                                      number.suffix.map_or("", |suffix| suffix.name()), ));
     }
 
     fn visit_decimal_expression(&mut self, _ast: &mut Ast, number: &DecimalExpr, _expr: &Expr) {
@@ -203,12 +302,31 @@ impl ASTVisitor for ASTPrinter {
                                       number.number, ));
     }  
 
+    fn visit_char_expression(&mut self, _ast: &mut Ast, character: &CharExpr, _expr: &Expr) {
+        self.add_text(&character.token.span.literal);
+    }
+
     fn visit_string_expression(&mut self, _ast: &mut Ast, string: &StringExpr, _expr: &Expr) {
         self.result.push_str(&format!("{}{}",
                                       Self::TEXT_COLOR.fg_str(),
                                       string.string, ));
     }
 
+    fn visit_interpolated_string_expression(&mut self, ast: &mut Ast, interpolated_string: &InterpolatedStringExpr, _expr: &Expr) {
+        for part in &interpolated_string.parts {
+            match part {
+                InterpolatedStringPart::Literal(value) => {
+                    self.add_text(value);
+                }
+                InterpolatedStringPart::Expr(expr) => {
+                    self.add_text("${");
+                    self.visit_expression(ast, *expr);
+                    self.add_text("}");
+                }
+            }
+        }
+    }
+
     fn visit_boolean_expression(&mut self, _ast: &mut Ast, boolean: &BoolExpr, _expr: &Expr) {
         self.add_boolean(boolean.value);
     }
@@ -223,17 +341,21 @@ impl ASTVisitor for ASTPrinter {
         self.result.push_str(&format!("{}{}",
                                       Self::TEXT_COLOR.fg_str(),
                                       unary_expression.operator.token.span.literal, ));
-        self.visit_expression(ast, unary_expression.operand);
+        let needs_parentheses = matches!(ast.query_expr(unary_expression.operand).kind, ExprKind::Binary(_));
+        self.visit_operand(ast, unary_expression.operand, needs_parentheses);
     }
 
     fn visit_binary_expression(&mut self, ast: &mut Ast, binary_expression: &BinaryExpr, _expr: &Expr) {
-        self.visit_expression(ast, binary_expression.left);
+        let operator = &binary_expression.operator.kind;
+        let left_needs_parentheses = Self::binary_operand_needs_parentheses(ast, binary_expression.left, operator, OperandSide::Left);
+        self.visit_operand(ast, binary_expression.left, left_needs_parentheses);
         self.add_whitespace();
         self.result.push_str(&format!("{}{}",
                                       Self::TEXT_COLOR.fg_str(),
                                       binary_expression.operator.token.span.literal, ));
         self.add_whitespace();
-        self.visit_expression(ast, binary_expression.right);
+        let right_needs_parentheses = Self::binary_operand_needs_parentheses(ast, binary_expression.right, operator, OperandSide::Right);
+        self.visit_operand(ast, binary_expression.right, right_needs_parentheses);
     }
 
     
@@ -1,8 +1,8 @@
-use crate::ast::{AssignExpr, Ast, BinaryExpr, BlockExpr, BoolExpr, CallExpr, Expr, ExprId, ExprKind, FunctionDeclaration, IfExpr, ItemId, ItemKind, LetStmt, NumberExpr, ParenthesizedExpr, ReturnStmt, Stmt, StmtId, StmtKind, UnaryExpr, VarExpr, WhileStmt};
+use crate::ast::{AssignExpr, Ast, BinaryExpr, BlockExpr, BoolExpr, BreakStmt, CallExpr, CastExpr, ContinueStmt, Expr, ExprId, ExprKind, ForStmt, FunctionDeclaration, IfExpr, IndexExpr, ItemId, ItemKind, LetStmt, LoopExpr, NumberExpr, ParenthesizedExpr, ReturnStmt, SliceExpr, Stmt, StmtId, StmtKind, UnaryExpr, VarExpr, WhileStmt};
 
 use crate::text::span::TextSpan;
 
-use super::{DecimalExpr, StringExpr};
+use super::{CharExpr, DecimalExpr, InterpolatedStringExpr, InterpolatedStringPart, StringExpr};
 
 pub trait ASTVisitor {
     fn visit_item(&mut self, ast: &mut Ast, item: ItemId) {
@@ -18,6 +18,9 @@ pub trait ASTVisitor {
             ItemKind::Function(func_decl) => {
                 self.visit_func_decl(ast, func_decl, item.id);
             }
+            ItemKind::Error(span) => {
+                self.visit_error(ast, span);
+            }
         }
     }
 
@@ -35,9 +38,21 @@ pub trait ASTVisitor {
             StmtKind::While(stmt) => {
                 self.visit_while_statement(ast, &stmt);
             }
+            StmtKind::For(stmt) => {
+                self.visit_for_statement(ast, stmt, &statement);
+            }
+            StmtKind::Break(stmt) => {
+                self.visit_break_statement(ast, stmt);
+            }
+            StmtKind::Continue(stmt) => {
+                self.visit_continue_statement(ast, stmt);
+            }
             StmtKind::Return(stmt) => {
                 self.visit_return_statement(ast, &stmt);
             }
+            StmtKind::Error(span) => {
+                self.visit_error(ast, span);
+            }
         }
     }
 
@@ -51,6 +66,30 @@ pub trait ASTVisitor {
         self.visit_expression(ast, while_statement.condition);
         self.visit_expression(ast, while_statement.body);
     }
+
+    fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, _stmt: &Stmt) {
+        self.visit_expression(ast, for_statement.start);
+        if let Some(range) = &for_statement.range {
+            self.visit_expression(ast, range.end);
+            if let Some(step) = &range.step {
+                self.visit_expression(ast, step.expr);
+            }
+        }
+        self.visit_expression(ast, for_statement.body);
+    }
+
+    fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, _expr: &Expr) {
+        self.visit_expression(ast, loop_expr.body);
+    }
+
+    fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+        if let Some(value) = &break_statement.value {
+            self.visit_expression(ast, *value);
+        }
+    }
+
+    fn visit_continue_statement(&mut self, _ast: &mut Ast, _continue_statement: &ContinueStmt) {}
+
     fn visit_block_expr(&mut self, ast: &mut Ast, block_expr: &BlockExpr, _expr: &Expr) {
         for stmt in &block_expr.stmts {
             self.visit_statement(ast, *stmt);
@@ -77,9 +116,15 @@ pub trait ASTVisitor {
             ExprKind::Decimal(decimal) =>{
                 self.visit_decimal_expression(ast, decimal, &expression);
             }
+            ExprKind::Char(character) => {
+                self.visit_char_expression(ast, character, &expression);
+            }
             ExprKind::String(string) => {
                 self.visit_string_expression(ast, string, &expression);
             }
+            ExprKind::InterpolatedString(interpolated_string) => {
+                self.visit_interpolated_string_expression(ast, interpolated_string, &expression);
+            }
             ExprKind::Binary(expr) => {
                 self.visit_binary_expression(ast, expr, &expression);
             }
@@ -110,9 +155,36 @@ pub trait ASTVisitor {
             ExprKind::Block(block_expr) => {
                 self.visit_block_expr(ast, &block_expr, &expression);
             }
+            ExprKind::Loop(loop_expr) => {
+                self.visit_loop_expression(ast, loop_expr, &expression);
+            }
+            ExprKind::Cast(cast_expr) => {
+                self.visit_cast_expression(ast, cast_expr, &expression);
+            }
+            ExprKind::Index(index_expr) => {
+                self.visit_index_expression(ast, index_expr, &expression);
+            }
+            ExprKind::Slice(slice_expr) => {
+                self.visit_slice_expression(ast, slice_expr, &expression);
+            }
         }
     }
 
+    fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, _expr: &Expr) {
+        self.visit_expression(ast, cast_expr.expr);
+    }
+
+    fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, _expr: &Expr) {
+        self.visit_expression(ast, index_expr.target);
+        self.visit_expression(ast, index_expr.index);
+    }
+
+    fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, _expr: &Expr) {
+        self.visit_expression(ast, slice_expr.target);
+        self.visit_expression(ast, slice_expr.start);
+        self.visit_expression(ast, slice_expr.end);
+    }
+
     fn visit_call_expression(&mut self, ast: &mut Ast, call_expression: &CallExpr, _expr: &Expr) {
         for argument in &call_expression.arguments {
             self.visit_expression(ast, *argument);
@@ -132,7 +204,17 @@ pub trait ASTVisitor {
 
     fn visit_decimal_expression(&mut self, ast: &mut Ast, number: &DecimalExpr, expr: &Expr);
 
+    fn visit_char_expression(&mut self, ast: &mut Ast, character: &CharExpr, expr: &Expr);
+
     fn visit_string_expression(&mut self, ast: &mut Ast, string: &StringExpr, expr: &Expr);
+
+    fn visit_interpolated_string_expression(&mut self, ast: &mut Ast, interpolated_string: &InterpolatedStringExpr, _expr: &Expr) {
+        for part in &interpolated_string.parts {
+            if let InterpolatedStringPart::Expr(expr) = part {
+                self.visit_expression(ast, *expr);
+            }
+        }
+    }
     
 
     fn visit_boolean_expression(&mut self, ast: &mut Ast, boolean: &BoolExpr, expr: &Expr);
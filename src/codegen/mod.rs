@@ -1,39 +1,80 @@
 use crate::ast::visitor::ASTVisitor;
+use fusion_compiler::Idx;
 use crate::ast::{
-    AssignExpr, Ast, BinOpKind, BinOperator, BinaryExpr, BlockExpr, BoolExpr, Expr, ExprId,
-    ExprKind, FunctionDeclaration, ItemId, ItemKind, LetStmt, NumberExpr, ParenthesizedExpr, Stmt,
-    StmtId, UnOpKind, UnOperator, UnaryExpr, VarExpr,
+    AssignExpr, Ast, BinOpKind, BinOperator, BinaryExpr, BlockExpr, BoolExpr, BreakStmt, CallExpr, CastExpr, CharExpr, ContinueStmt, Expr, ExprId,
+    ExprKind, ForStmt, FunctionDeclaration, IndexExpr, InterpolatedStringExpr, InterpolatedStringPart, ItemId,
+    ItemKind, LetStmt, LoopExpr, LoopLabel, NumberExpr, OperandSide, ParenthesizedExpr, SliceExpr, Stmt, StmtId, UnOpKind, UnOperator,
+    UnaryExpr, VarExpr, WhileStmt,
 };
-use crate::compilation_unit::{GlobalScope, VariableIdx};
+use crate::ast::lexer::Token;
+use crate::compilation_unit::{BuiltinFunction, FunctionIdx, GlobalScope, VariableIdx};
 use crate::text::span::TextSpan;
-use crate::typings::Type;
+use crate::text::SourceText;
+use crate::typings::{IntegerType, Type};
+
+const RUNTIME: &str = include_str!("runtime.c");
+
+/// A loop being transpiled. Exits that skip the innermost C loop jump to the
+/// `fx_break_<id>` and `fx_continue_<id>` labels, which are only emitted when used.
+struct CLoop {
+    id: usize,
+    label: Option<String>,
+    // The variable holding the `break` value of a `loop` expression
+    result: Option<String>,
+    break_label_used: bool,
+    continue_label_used: bool,
+}
 
 pub struct CTranspiler<'a> {
     pub result: String,
     pub indent: usize,
     pub global_scope: &'a GlobalScope,
+    // For the locations runtime errors are reported at
+    text: &'a SourceText,
     pub l_value_stack: Vec<(VariableIdx, ExprId)>,
+    loops: Vec<CLoop>,
+    loop_count: usize,
 }
 
 impl<'a> CTranspiler<'a> {
-    pub fn new(global_scope: &'a GlobalScope) -> Self {
+    pub fn new(global_scope: &'a GlobalScope, text: &'a SourceText) -> Self {
         Self {
             result: String::new(),
             indent: 0,
             global_scope,
+            text,
             l_value_stack: Vec::new(),
+            loops: Vec::new(),
+            loop_count: 0,
         }
     }
 
     pub fn transpile(mut self, ast: &mut Ast) -> String {
         let items = ast.items.clone();
 
+        self.result.push_str(RUNTIME);
+        self.write_newline();
+        // Globals are declared at file scope for the functions, the statements in `main`
+        // initialize them
+        for variable_idx in self.global_scope.global_variables.iter() {
+            let variable = self.global_scope.variables.get(*variable_idx);
+            self.write_type(&variable.ty);
+            self.result.push_str(&format!(" {};\n", self.variable_name(*variable_idx)));
+        }
+        // Prototypes first, so that functions can call the ones defined after them
+        for item in items.iter() {
+            if let ItemKind::Function(function_decl) = &item.kind {
+                self.write_function_signature(function_decl.idx);
+                self.result.push_str(";\n");
+            }
+        }
         for item in items.iter() {
             match &item.kind {
                 ItemKind::Stmt(_stmt) => {}
                 ItemKind::Function(function_decl) => {
                     self.visit_func_decl(ast, function_decl, item.id);
                 }
+                ItemKind::Error(_) => {}
             }
         }
         self.result.push_str("int main() {\n");
@@ -43,7 +84,7 @@ impl<'a> CTranspiler<'a> {
                 ItemKind::Stmt(stmt) => {
                     self.visit_statement(ast, *stmt);
                 }
-                ItemKind::Function(_) => {}
+                ItemKind::Function(_) | ItemKind::Error(_) => {}
             }
         }
         self.write_ident();
@@ -52,11 +93,31 @@ impl<'a> CTranspiler<'a> {
         return self.result;
     }
 
+    fn write_function_signature(&mut self, function_idx: FunctionIdx) {
+        let function = self.global_scope.functions.get(function_idx);
+        self.write_type(&function.return_type);
+        self.write_whitespace();
+        self.result.push_str(&function.name);
+        self.result.push_str("(");
+        for (i, parameter) in function.parameters.iter().enumerate() {
+            let parameter = self.global_scope.variables.get(*parameter);
+            self.write_type(&parameter.ty);
+            self.write_whitespace();
+            self.result.push_str(&parameter.name);
+            if i != function.parameters.len() - 1 {
+                self.result.push_str(", ");
+            }
+        }
+        self.result.push(')');
+    }
+
     fn transpile_type(ty: &Type) -> String {
         return match ty {
-            Type::Int => "int".to_string(),
+            Type::Int | Type::Integer(_) => Self::transpile_integer_type(ty.integer_type().unwrap()).to_string(),
             Type::Bool => "int".to_string(),
-            Type::Float => "float".to_string(),
+            Type::Float => "double".to_string(),
+            // A code point
+            Type::Char => "uint32_t".to_string(),
             Type::String => "char*".to_string(),
             Type::Void => "void".to_string(),
             Type::Unresolved => panic!("Unresolved type"),
@@ -64,24 +125,68 @@ impl<'a> CTranspiler<'a> {
         };
     }
 
+    fn transpile_integer_type(integer: IntegerType) -> &'static str {
+        match integer {
+            IntegerType::I8 => "int8_t",
+            IntegerType::I16 => "int16_t",
+            IntegerType::I32 => "int32_t",
+            IntegerType::I64 => "int64_t",
+            IntegerType::U8 => "uint8_t",
+            IntegerType::U16 => "uint16_t",
+            IntegerType::U32 => "uint32_t",
+            IntegerType::U64 => "uint64_t",
+            IntegerType::Usize => "size_t",
+        }
+    }
+
+    /// The `<stdint.h>` macros for the bounds of a signed type.
+    fn integer_bounds(integer: IntegerType) -> String {
+        let name = Self::transpile_integer_type(integer).trim_end_matches("_t").to_uppercase();
+        format!("{}_MIN, {}_MAX", name, name)
+    }
+
+    /// The `<stdint.h>` macro for the maximum of any integer type.
+    fn integer_max(integer: IntegerType) -> String {
+        format!("{}_MAX", Self::transpile_integer_type(integer).trim_end_matches("_t").to_uppercase())
+    }
+
+    /// An integer constant of type `integer`. C has no negative literals and no way to
+    /// write the minimum of `int64_t` as a negated one.
+    fn integer_constant(value: i128, integer: IntegerType) -> String {
+        match value {
+            _ if value == i64::MIN as i128 => "INT64_MIN".to_string(),
+            _ if value > i64::MAX as i128 => format!("{}ULL", value),
+            _ if integer.is_signed() => value.to_string(),
+            _ => format!("{}U", value),
+        }
+    }
+
+    /// The runtime function converting a value of `ty` to a string, strings need none.
+    fn to_string_function(ty: &Type) -> Option<&'static str> {
+        match ty {
+            Type::Int | Type::Integer(_) if ty.integer_type().unwrap().is_signed() => Some("fx_int_to_string"),
+            Type::Int | Type::Integer(_) => Some("fx_uint_to_string"),
+            Type::Float => Some("fx_float_to_string"),
+            Type::Bool => Some("fx_bool_to_string"),
+            Type::Char => Some("fx_char_to_string"),
+            _ => None,
+        }
+    }
+
     fn transpile_unary_operator(&self, operator: &UnOperator) -> &'static str {
         return match &operator.kind {
-            UnOpKind::Minus => "-",
+            UnOpKind::Minus | UnOpKind::MinusDecimal => "-",
             UnOpKind::BitwiseNot => "~",
+            UnOpKind::LogicalNot => "!",
         };
     }
 
     fn transpile_binary_operator(&self, operator: &BinOperator) -> &'static str {
         return match &operator.kind {
-            BinOpKind::Plus => "+",
             BinOpKind::PlusDecimal => "+",
             BinOpKind::MinusDecimal => "-",
             BinOpKind::MultiplyDecimal => "*",
             BinOpKind::DivideDecimal => "/",
-            BinOpKind::PlusString => "+",
-            BinOpKind::Minus => "-",
-            BinOpKind::Multiply => "*",
-            BinOpKind::Divide => "/",
             BinOpKind::Equals => "==",
             BinOpKind::NotEquals => "!=",
             BinOpKind::LessThan => "<",
@@ -89,10 +194,101 @@ impl<'a> CTranspiler<'a> {
             BinOpKind::BitwiseAnd => "&",
             BinOpKind::BitwiseOr => "|",
             BinOpKind::BitwiseXor => "^",
-            BinOpKind::Power => panic!("Power operator not supported"),
             BinOpKind::LessThanOrEqual => "<=",
             BinOpKind::GreaterThanOrEqual => ">=",
+            BinOpKind::LessThanDecimal => "<",
+            BinOpKind::LessThanOrEqualDecimal => "<=",
+            BinOpKind::GreaterThanDecimal => ">",
+            BinOpKind::GreaterThanOrEqualDecimal => ">=",
+            BinOpKind::LessThanChar => "<",
+            BinOpKind::LessThanOrEqualChar => "<=",
+            BinOpKind::GreaterThanChar => ">",
+            BinOpKind::GreaterThanOrEqualChar => ">=",
+            BinOpKind::LogicalAnd => "&&",
+            BinOpKind::LogicalOr => "||",
+            BinOpKind::Plus | BinOpKind::Minus | BinOpKind::Multiply | BinOpKind::Divide | BinOpKind::Power
+            | BinOpKind::Modulo | BinOpKind::ShiftLeft | BinOpKind::ShiftRight | BinOpKind::UnsignedShiftRight
+            | BinOpKind::PlusString | BinOpKind::EqualsString | BinOpKind::NotEqualsString | BinOpKind::LessThanString
+            | BinOpKind::LessThanOrEqualString | BinOpKind::GreaterThanString | BinOpKind::GreaterThanOrEqualString => {
+                unreachable!("Transpiled to a runtime function call")
+            }
+        };
+    }
+
+    /// The runtime function implementing `operator` for operands of `operand_type`. The
+    /// integer arithmetic and shifts have the semantics of the evaluator, where C would be
+    /// undefined (signed overflow, shifts of the width or more, division by zero) or
+    /// would promote to `int`. C has no string operators.
+    fn binary_operator_runtime_function(operator: &BinOpKind, operand_type: &Type) -> Option<&'static str> {
+        if let Some(integer) = operand_type.integer_type() {
+            return match (operator, integer.is_signed()) {
+                (BinOpKind::Plus, true) => Some("fx_add"),
+                (BinOpKind::Minus, true) => Some("fx_sub"),
+                (BinOpKind::Multiply, true) => Some("fx_mul"),
+                (BinOpKind::Divide, true) => Some("fx_div"),
+                (BinOpKind::Modulo, true) => Some("fx_mod"),
+                (BinOpKind::Power, true) => Some("fx_pow"),
+                (BinOpKind::Plus, false) => Some("fx_wrapping_add"),
+                (BinOpKind::Minus, false) => Some("fx_wrapping_sub"),
+                (BinOpKind::Multiply, false) => Some("fx_wrapping_mul"),
+                (BinOpKind::Divide, false) => Some("fx_unsigned_div"),
+                (BinOpKind::Modulo, false) => Some("fx_unsigned_mod"),
+                (BinOpKind::Power, false) => Some("fx_wrapping_pow"),
+                (BinOpKind::ShiftLeft, _) => Some("fx_shl"),
+                (BinOpKind::ShiftRight, true) => Some("fx_shr"),
+                (BinOpKind::ShiftRight, false) | (BinOpKind::UnsignedShiftRight, _) => Some("fx_ushr"),
+                _ => None,
+            };
+        }
+        match operator {
+            BinOpKind::PlusString => Some("fx_string_concat"),
+            BinOpKind::EqualsString => Some("fx_string_equals"),
+            BinOpKind::NotEqualsString => Some("fx_string_not_equals"),
+            BinOpKind::LessThanString => Some("fx_string_less_than"),
+            BinOpKind::LessThanOrEqualString => Some("fx_string_less_than_or_equal"),
+            BinOpKind::GreaterThanString => Some("fx_string_greater_than"),
+            BinOpKind::GreaterThanOrEqualString => Some("fx_string_greater_than_or_equal"),
+            _ => None,
+        }
+    }
+
+    /// The call `operator` is transpiled to, as the code before the left operand and the
+    /// code after the right one, which are separated by a comma. Integer results are cast
+    /// back from the 64 bit types the runtime computes with.
+    fn binary_operator_runtime_call(&self, operator: &BinOpKind, operand_type: &Type, span: &TextSpan) -> Option<(String, String)> {
+        let function = Self::binary_operator_runtime_function(operator, operand_type)?;
+        let Some(integer) = operand_type.integer_type() else {
+            return Some((format!("{}(", function), ")".to_string()));
         };
+        let arguments = match operator {
+            BinOpKind::Plus | BinOpKind::Minus | BinOpKind::Multiply | BinOpKind::Divide | BinOpKind::Power if integer.is_signed() => {
+                format!(", {}, {}", Self::integer_bounds(integer), self.location(span))
+            }
+            BinOpKind::Divide | BinOpKind::Modulo => format!(", {}", self.location(span)),
+            BinOpKind::ShiftLeft | BinOpKind::ShiftRight | BinOpKind::UnsignedShiftRight => format!(", {}", integer.bits()),
+            _ => String::new(),
+        };
+        Some((format!("({}) {}(", Self::transpile_integer_type(integer), function), format!("{})", arguments)))
+    }
+
+    /// Whether `operand` needs parentheses under C's precedence rules, which differ from
+    /// ours for the bitwise operators. Operators transpiled to runtime calls never do.
+    fn binary_operand_needs_parentheses(ast: &Ast, operand: ExprId, operator: &BinOpKind, side: OperandSide) -> bool {
+        match &ast.query_expr(operand).kind {
+            ExprKind::Binary(operand) if Self::binary_operator_runtime_function(&operand.operator.kind, &ast.query_expr(operand.left).ty).is_some() => false,
+            ExprKind::Binary(operand) => operator.operand_needs_parentheses(&operand.operator.kind, side, BinOpKind::c_precedence),
+            _ => false,
+        }
+    }
+
+    fn visit_operand(&mut self, ast: &mut Ast, operand: ExprId, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.result.push('(');
+        }
+        self.visit_expression(ast, operand);
+        if needs_parentheses {
+            self.result.push(')');
+        }
     }
 
     fn is_valid_r_value(&self, ast: &Ast, expr: ExprId) -> bool {
@@ -100,7 +296,14 @@ impl<'a> CTranspiler<'a> {
         return match &expr.kind {
             ExprKind::Number(_) => true,
             ExprKind::Decimal(_) => true,
+            ExprKind::Char(_) => true,
             ExprKind::String(_) => true,
+            ExprKind::InterpolatedString(interpolated_string) => {
+                interpolated_string.parts.iter().all(|part| match part {
+                    InterpolatedStringPart::Literal(_) => true,
+                    InterpolatedStringPart::Expr(expr) => self.is_valid_r_value(ast, *expr),
+                })
+            }
             ExprKind::Binary(binary_expr) => {
                 let left = self.is_valid_r_value(ast, binary_expr.left);
                 let right = self.is_valid_r_value(ast, binary_expr.right);
@@ -123,10 +326,107 @@ impl<'a> CTranspiler<'a> {
             }
             ExprKind::If(_) => false,
             ExprKind::Block(_) => false,
+            ExprKind::Loop(_) => false,
+            ExprKind::Cast(cast_expr) => self.is_valid_r_value(ast, cast_expr.expr),
+            ExprKind::Index(index_expr) => {
+                self.is_valid_r_value(ast, index_expr.target) && self.is_valid_r_value(ast, index_expr.index)
+            }
+            ExprKind::Slice(slice_expr) => {
+                self.is_valid_r_value(ast, slice_expr.target) && self.is_valid_r_value(ast, slice_expr.start)
+                    && self.is_valid_r_value(ast, slice_expr.end)
+            }
             ExprKind::Error(_) => panic!("Error expression"),
         };
     }
 
+    /// Turns a decoded string value back into a C string literal, quotes included.
+    /// Non-ASCII characters are written byte by byte as octal escapes, which unlike
+    /// `\x` escapes cannot swallow the characters that follow them.
+    fn escape_string(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len() + 2);
+        escaped.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\t' => escaped.push_str("\\t"),
+                '\r' => escaped.push_str("\\r"),
+                ' '..='~' => escaped.push(c),
+                _ => {
+                    let mut buffer = [0; 4];
+                    for byte in c.encode_utf8(&mut buffer).bytes() {
+                        escaped.push_str(&format!("\\{:03o}", byte));
+                    }
+                }
+            }
+        }
+        escaped.push('"');
+        escaped
+    }
+
+    /// The location of `span` as a C string literal, `"<column>:<line>"` like in diagnostics.
+    fn location(&self, span: &TextSpan) -> String {
+        let line_index = self.text.line_index(span.start);
+        let line_start = self.text.line_start(line_index);
+        let column = self.text.get_line(line_index)[..span.start - line_start].chars().count();
+        format!("\"{}:{}\"", column + 1, line_index + 1)
+    }
+
+    /// `1` if `expr` has an unsigned integer type, telling the runtime how to print its value.
+    fn is_unsigned(ast: &Ast, expr: ExprId) -> u8 {
+        let integer = ast.query_expr(expr).ty.integer_type();
+        integer.is_some_and(|integer| !integer.is_signed()) as u8
+    }
+
+    fn enter_loop(&mut self, label: &Option<LoopLabel>, result: Option<String>) -> usize {
+        self.loop_count += 1;
+        self.loops.push(CLoop {
+            id: self.loop_count,
+            label: label.as_ref().map(|label| label.name().to_string()),
+            result,
+            break_label_used: false,
+            continue_label_used: false,
+        });
+        self.loop_count
+    }
+
+    /// Writes the body of the innermost loop, then its closing brace. The `break` label,
+    /// if used, follows the brace and takes the statement's trailing `;`.
+    fn write_loop_body_and_exit(&mut self, ast: &mut Ast, body: ExprId) {
+        self.indent += 1;
+        match &ast.query_expr(body).kind {
+            ExprKind::Block(_) => self.visit_expression(ast, body),
+            _ => {
+                self.write_ident();
+                self.visit_expression(ast, body);
+                self.result.push_str(";\n");
+            }
+        }
+        let c_loop = self.loops.pop().unwrap();
+        if c_loop.continue_label_used {
+            self.write_ident();
+            self.result.push_str(&format!("fx_continue_{}: ;\n", c_loop.id));
+        }
+        self.indent -= 1;
+        self.write_ident();
+        self.result.push('}');
+        if c_loop.break_label_used {
+            self.write_newline();
+            self.write_ident();
+            self.result.push_str(&format!("fx_break_{}:", c_loop.id));
+        }
+    }
+
+    /// The index in `loops` of the loop a `break` or `continue` exits. The resolver has
+    /// already reported exits without a loop.
+    fn loop_target(&self, label: &Option<Token>) -> usize {
+        match label {
+            Some(label) => self.loops.iter().rposition(|c_loop| c_loop.label.as_deref() == Some(label.span.literal.as_str())),
+            None => self.loops.len().checked_sub(1),
+        }.expect("Loop exit without a loop")
+    }
+
     fn write_newline(&mut self) {
         self.result.push('\n');
     }
@@ -137,6 +437,16 @@ impl<'a> CTranspiler<'a> {
         }
     }
 
+    /// The name of the variable in C. Globals are at file scope, where their names could
+    /// clash with the C library or with another global that a top-level `let` redeclares.
+    fn variable_name(&self, variable_idx: VariableIdx) -> String {
+        let name = &self.global_scope.variables.get(variable_idx).name;
+        match self.global_scope.global_variables.contains(&variable_idx) {
+            true => format!("fx_global_{}_{}", name, variable_idx.as_index()),
+            false => name.clone(),
+        }
+    }
+
     fn write_type(&mut self, ty: &Type) {
         self.result.push_str(&CTranspiler::transpile_type(ty));
     }
@@ -153,21 +463,8 @@ impl ASTVisitor for CTranspiler<'_> {
         func_decl: &FunctionDeclaration,
         _item_id: ItemId,
     ) {
-        let function = self.global_scope.functions.get(func_decl.idx);
-        self.write_type(&function.return_type);
-        self.write_whitespace();
-        self.result.push_str(&function.name);
-        self.result.push_str("(");
-        for (i, parameter) in function.parameters.iter().enumerate() {
-            let parameter = self.global_scope.variables.get(*parameter);
-            self.write_type(&parameter.ty);
-            self.write_whitespace();
-            self.result.push_str(&parameter.name);
-            if i != function.parameters.len() - 1 {
-                self.result.push_str(", ");
-            }
-        }
-        self.result.push_str(") {\n");
+        self.write_function_signature(func_decl.idx);
+        self.result.push_str(" {\n");
         self.indent += 1;
         self.visit_expression(ast, func_decl.body);
         self.indent -= 1;
@@ -181,11 +478,126 @@ impl ASTVisitor for CTranspiler<'_> {
         self.write_newline();
     }
 
+    /// Lowers to a C `for` loop. The end and the step are copied into variables named
+    /// after the loop variable's index, so they are evaluated once like in the evaluator.
+    /// Loops over strings walk a pointer through the string, decoding one character at a time.
+    fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, _stmt: &Stmt) {
+        let variable = self.global_scope.variables.get(for_statement.variable_idx);
+        let index = for_statement.variable_idx.as_index();
+        let Some(range) = &for_statement.range else {
+            let (at, next) = (format!("fx_for_at_{}", index), format!("fx_for_next_{}", index));
+            self.result.push_str(&format!("for (const char *{} = ", at));
+            self.visit_expression(ast, for_statement.start);
+            self.result.push_str(&format!(", *{next}; *{at} != '\\0'; {at} = {next}) {{\n", at = at, next = next));
+            self.indent += 1;
+            self.write_ident();
+            self.write_type(&variable.ty);
+            self.result.push_str(&format!(" {} = fx_decode_char({}, &{});\n", variable.name, at, next));
+            self.indent -= 1;
+            self.enter_loop(&for_statement.label, None);
+            self.write_loop_body_and_exit(ast, for_statement.body);
+            return;
+        };
+        let (end, step, done) = (format!("fx_for_end_{}", index), format!("fx_for_step_{}", index), format!("fx_for_done_{}", index));
+        self.result.push_str("for (");
+        self.write_type(&variable.ty);
+        self.result.push_str(&format!(" {} = ", variable.name));
+        self.visit_expression(ast, for_statement.start);
+        self.result.push_str(&format!(", {} = ", end));
+        self.visit_expression(ast, range.end);
+        self.result.push_str(&format!(", {} = ", step));
+        match &range.step {
+            Some(for_step) => {
+                let is_unsigned = variable.ty.integer_type().is_some_and(|integer| !integer.is_signed());
+                self.result.push_str(if is_unsigned { "fx_unsigned_for_step(" } else { "fx_for_step(" });
+                self.visit_expression(ast, for_step.expr);
+                let location = self.location(&ast.query_expr(for_step.expr).span(ast));
+                self.result.push_str(&format!(", {})", location));
+            }
+            None => self.result.push('1'),
+        }
+        // The loop ends instead of overflowing when the next value doesn't fit, like
+        // after the last iteration of `0..=9223372036854775807`
+        let comparison = if for_statement.is_inclusive() { "<=" } else { "<" };
+        self.result.push_str(&format!(
+            ", {done} = 0; !{done} && {name} {} {}; {done} = __builtin_add_overflow({name}, {}, &{name})) {{\n",
+            comparison, end, step, done = done, name = variable.name,
+        ));
+        self.enter_loop(&for_statement.label, None);
+        self.write_loop_body_and_exit(ast, for_statement.body);
+    }
+
+    fn visit_while_statement(&mut self, ast: &mut Ast, while_statement: &WhileStmt) {
+        self.enter_loop(&while_statement.label, None);
+        self.result.push_str("while (");
+        self.visit_expression(ast, while_statement.condition);
+        self.result.push_str(") {\n");
+        self.write_loop_body_and_exit(ast, while_statement.body);
+    }
+
+    /// A `loop` with a `break` value becomes a statement expression (a GNU extension
+    /// that gcc and clang support) ending in the variable the value is stored in.
+    fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, expr: &Expr) {
+        if matches!(expr.ty, Type::Void) {
+            self.enter_loop(&loop_expr.label, None);
+            self.result.push_str("for (;;) {\n");
+            self.write_loop_body_and_exit(ast, loop_expr.body);
+            return;
+        }
+        let result = format!("fx_loop_{}", self.loop_count + 1);
+        self.enter_loop(&loop_expr.label, Some(result.clone()));
+        self.result.push_str("({\n");
+        self.indent += 1;
+        self.write_ident();
+        self.write_type(&expr.ty);
+        self.result.push_str(&format!(" {};\n", result));
+        self.write_ident();
+        self.result.push_str("for (;;) {\n");
+        self.write_loop_body_and_exit(ast, loop_expr.body);
+        self.result.push_str(";\n");
+        self.write_ident();
+        self.result.push_str(&format!("{};\n", result));
+        self.indent -= 1;
+        self.write_ident();
+        self.result.push_str("})");
+    }
+
+    fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+        let target = self.loop_target(&break_statement.label);
+        let is_innermost = target == self.loops.len() - 1;
+        let exit = if is_innermost {
+            "break".to_string()
+        } else {
+            self.loops[target].break_label_used = true;
+            format!("goto fx_break_{}", self.loops[target].id)
+        };
+        match (&break_statement.value, self.loops[target].result.clone()) {
+            (Some(value), Some(result)) => {
+                self.result.push_str(&format!("{{ {} = ", result));
+                self.visit_expression(ast, *value);
+                self.result.push_str(&format!("; {}; }}", exit));
+            }
+            _ => self.result.push_str(&exit),
+        }
+    }
+
+    fn visit_continue_statement(&mut self, _ast: &mut Ast, continue_statement: &ContinueStmt) {
+        let target = self.loop_target(&continue_statement.label);
+        if target == self.loops.len() - 1 {
+            self.result.push_str("continue");
+        } else {
+            self.loops[target].continue_label_used = true;
+            self.result.push_str(&format!("goto fx_continue_{}", self.loops[target].id));
+        }
+    }
+
     fn visit_let_statement(&mut self, ast: &mut Ast, let_statement: &LetStmt, _stmt: &Stmt) {
         let variable = self.global_scope.variables.get(let_statement.variable_idx);
-        self.write_type(&variable.ty);
-        self.write_whitespace();
-        self.result.push_str(&variable.name);
+        if !self.global_scope.global_variables.contains(&let_statement.variable_idx) {
+            self.write_type(&variable.ty);
+            self.write_whitespace();
+        }
+        self.result.push_str(&self.variable_name(let_statement.variable_idx));
         self.result.push_str(" = ");
         self.visit_expression(ast, let_statement.initializer);
     }
@@ -196,15 +608,12 @@ impl ASTVisitor for CTranspiler<'_> {
         variable_expression: &VarExpr,
         _expr: &Expr,
     ) {
-        let variable = self
-            .global_scope
-            .variables
-            .get(variable_expression.variable_idx);
-        self.result.push_str(&variable.name);
+        self.result.push_str(&self.variable_name(variable_expression.variable_idx));
     }
 
-    fn visit_number_expression(&mut self, _ast: &mut Ast, number: &NumberExpr, _expr: &Expr) {
-        self.result.push_str(&number.number.to_string());
+    fn visit_number_expression(&mut self, _ast: &mut Ast, number: &NumberExpr, expr: &Expr) {
+        let integer = expr.ty.integer_type().expect("Literals have an integer type");
+        self.result.push_str(&Self::integer_constant(number.number as i128, integer));
     }
 
     fn visit_decimal_expression(
@@ -213,7 +622,13 @@ impl ASTVisitor for CTranspiler<'_> {
         decimal: &crate::ast::DecimalExpr,
         _expr: &Expr,
     ) {
-        self.result.push_str(&decimal.number.to_string());
+        // `{:?}` keeps the decimal point, so that C doesn't treat `2.0` as an int
+        self.result.push_str(&format!("{:?}", decimal.number));
+    }
+
+    /// Chars are emitted as their code point.
+    fn visit_char_expression(&mut self, _ast: &mut Ast, character: &CharExpr, _expr: &Expr) {
+        self.result.push_str(&(character.value as u32).to_string());
     }
 
     fn visit_string_expression(
@@ -222,8 +637,43 @@ impl ASTVisitor for CTranspiler<'_> {
         string: &crate::ast::StringExpr,
         _expr: &Expr,
     ) {
-        self.result.push_str(&string.string);
+        self.result.push_str(&CTranspiler::escape_string(&string.string));
+    }
+    /// Lowers `"a${b}c"` to `fx_string_concat(fx_string_concat("a", <b as string>), "c")`.
+    fn visit_interpolated_string_expression(
+        &mut self,
+        ast: &mut Ast,
+        interpolated_string: &InterpolatedStringExpr,
+        _expr: &Expr,
+    ) {
+        let concatenations = interpolated_string.parts.len().saturating_sub(1);
+        self.result.push_str(&"fx_string_concat(".repeat(concatenations));
+        for (i, part) in interpolated_string.parts.iter().enumerate() {
+            if i != 0 {
+                self.result.push_str(", ");
+            }
+            match part {
+                InterpolatedStringPart::Literal(value) => {
+                    self.result.push_str(&CTranspiler::escape_string(value));
+                }
+                InterpolatedStringPart::Expr(expr) => {
+                    let to_string = Self::to_string_function(&ast.query_expr(*expr).ty);
+                    if let Some(to_string) = to_string {
+                        self.result.push_str(to_string);
+                        self.result.push('(');
+                    }
+                    self.visit_expression(ast, *expr);
+                    if to_string.is_some() {
+                        self.result.push(')');
+                    }
+                }
+            }
+            if i != 0 {
+                self.result.push(')');
+            }
+        }
     }
+
     fn visit_boolean_expression(&mut self, _ast: &mut Ast, boolean: &BoolExpr, _expr: &Expr) {
         self.result.push_str(if boolean.value { "1" } else { "0" });
     }
@@ -232,46 +682,178 @@ impl ASTVisitor for CTranspiler<'_> {
         self.result.push_str("/* error */");
     }
 
+    fn visit_call_expression(&mut self, ast: &mut Ast, call_expression: &CallExpr, expr: &Expr) {
+        let name = call_expression.function_name();
+        // The parse functions report invalid input as runtime errors at the call
+        let (name, reports_errors) = match (self.global_scope.lookup_function(name), BuiltinFunction::lookup(name)) {
+            (None, Some(BuiltinFunction::ParseInt)) => ("fx_parse_int", true),
+            (None, Some(BuiltinFunction::ParseFloat)) => ("fx_parse_float", true),
+            (None, Some(BuiltinFunction::Len)) => ("fx_string_length", false),
+            _ => (name, false),
+        };
+        self.result.push_str(name);
+        self.result.push('(');
+        for (i, argument) in call_expression.arguments.iter().enumerate() {
+            if i != 0 {
+                self.result.push_str(", ");
+            }
+            self.visit_expression(ast, *argument);
+        }
+        if reports_errors {
+            let location = self.location(&expr.span(ast));
+            self.result.push_str(&format!(", {}", location));
+        }
+        self.result.push(')');
+    }
+
+    /// Conversions that are undefined in C for some values (`float as int` out of range
+    /// or NaN), the saturating `char as u8`, `int as char` and the ones to strings go
+    /// through the runtime. Casts between integer types keep the low bits in C as well.
+    fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, expr: &Expr) {
+        let from = &ast.query_expr(cast_expr.expr).ty;
+        let integer = expr.ty.integer_type();
+        let (prefix, suffix) = match (from, &expr.ty) {
+            (Type::Int | Type::Integer(_), Type::Float) => ("(double) (".to_string(), ")".to_string()),
+            (Type::Float, Type::Int | Type::Integer(_)) if integer.unwrap().is_signed() => {
+                let integer = integer.unwrap();
+                let type_name = Self::transpile_integer_type(integer);
+                (format!("({}) fx_float_to_int(", type_name), format!(", {})", Self::integer_bounds(integer)))
+            }
+            (Type::Float | Type::Char, Type::Int | Type::Integer(_)) => {
+                let integer = integer.unwrap();
+                let function = if matches!(from, Type::Float) { "fx_float_to_uint" } else { "fx_char_to_integer" };
+                let type_name = Self::transpile_integer_type(integer);
+                (format!("({}) {}(", type_name, function), format!(", {})", Self::integer_max(integer)))
+            }
+            (Type::Int | Type::Integer(_), Type::Bool) => ("(".to_string(), " != 0)".to_string()),
+            (Type::Int | Type::Integer(_), Type::Char) => ("fx_int_to_char(".to_string(), ")".to_string()),
+            (_, Type::String) => match Self::to_string_function(from) {
+                Some(to_string) => (format!("{}(", to_string), ")".to_string()),
+                None => ("(".to_string(), ")".to_string()),
+            },
+            (_, Type::Int | Type::Integer(_)) => {
+                (format!("({}) (", Self::transpile_integer_type(integer.unwrap())), ")".to_string())
+            }
+            _ => ("(".to_string(), ")".to_string()),
+        };
+        self.result.push_str(&prefix);
+        self.visit_expression(ast, cast_expr.expr);
+        self.result.push_str(&suffix);
+    }
+
+    fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, expr: &Expr) {
+        self.result.push_str("fx_string_index(");
+        self.visit_expression(ast, index_expr.target);
+        self.result.push_str(", ");
+        self.visit_expression(ast, index_expr.index);
+        let is_unsigned = Self::is_unsigned(ast, index_expr.index);
+        self.result.push_str(&format!(", {}, {})", is_unsigned, self.location(&expr.span(ast))));
+    }
+
+    fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, expr: &Expr) {
+        self.result.push_str("fx_string_slice(");
+        self.visit_expression(ast, slice_expr.target);
+        self.result.push_str(", ");
+        self.visit_expression(ast, slice_expr.start);
+        self.result.push_str(", ");
+        self.visit_expression(ast, slice_expr.end);
+        let inclusive = if slice_expr.is_inclusive() { 1 } else { 0 };
+        let is_unsigned = Self::is_unsigned(ast, slice_expr.start);
+        self.result.push_str(&format!(", {}, {}, {})", inclusive, is_unsigned, self.location(&expr.span(ast))));
+    }
+
     fn visit_unary_expression(
         &mut self,
         ast: &mut Ast,
         unary_expression: &UnaryExpr,
-        _expr: &Expr,
+        expr: &Expr,
     ) {
+        let operand = ast.query_expr(unary_expression.operand);
+        match (&unary_expression.operator.kind, &operand.kind, expr.ty.integer_type()) {
+            (UnOpKind::Minus, ExprKind::Number(number), Some(integer)) => {
+                self.result.push_str(&Self::integer_constant(-(number.number as i128), integer));
+                return;
+            }
+            // `-x` overflows for the minimum. The operand is signed, the resolver rejects `-`
+            // on unsigned types, so this is `fx_sub` and never the wrapping subtraction.
+            (UnOpKind::Minus, _, Some(_)) => {
+                let (prefix, suffix) = self.binary_operator_runtime_call(&BinOpKind::Minus, &expr.ty, &expr.span(ast)).unwrap();
+                self.result.push_str(&format!("{}0, ", prefix));
+                self.visit_expression(ast, unary_expression.operand);
+                self.result.push_str(&suffix);
+                return;
+            }
+            // `~` promotes narrow operands to `int`, so the result is cast back
+            (UnOpKind::BitwiseNot, _, Some(integer)) => {
+                self.result.push_str(&format!("({}) ", Self::transpile_integer_type(integer)));
+            }
+            _ => {}
+        }
         self.result
             .push_str(self.transpile_unary_operator(&unary_expression.operator));
-        self.visit_expression(ast, unary_expression.operand);
+        // Parenthesize nested unary operators too, `- -a` must not become `--a`
+        let needs_parentheses = matches!(
+            ast.query_expr(unary_expression.operand).kind,
+            ExprKind::Binary(_) | ExprKind::Unary(_)
+        );
+        self.visit_operand(ast, unary_expression.operand, needs_parentheses);
     }
 
     fn visit_assignment_expression(
         &mut self,
         ast: &mut Ast,
         assignment_expression: &AssignExpr,
-        _expr: &Expr,
+        expr: &Expr,
     ) {
-        self.result.push_str(
-            &self
-                .global_scope
-                .variables
-                .get(assignment_expression.variable_idx)
-                .name,
-        );
-        self.result.push_str(" = ");
+        let variable = self.global_scope.variables.get(assignment_expression.variable_idx);
+        let name = self.variable_name(assignment_expression.variable_idx);
+        let Some(operator) = &assignment_expression.operator else {
+            self.result.push_str(&format!("{} = ", name));
+            self.visit_expression(ast, assignment_expression.expression);
+            return;
+        };
+        // The value is computed before the target is read, like in the evaluator. C leaves the
+        // order unspecified, and `y ^= (y = 6)` would even be undefined.
+        let value = format!("fx_value_{}", expr.id.as_index());
+        self.result.push_str("({ ");
+        self.write_type(&ast.query_expr(assignment_expression.expression).ty);
+        self.result.push_str(&format!(" {} = ", value));
         self.visit_expression(ast, assignment_expression.expression);
+        match self.binary_operator_runtime_call(&operator.kind, &variable.ty, &expr.span(ast)) {
+            Some((prefix, suffix)) => {
+                self.result.push_str(&format!("; {name} = {}{name}, {}{}; }})", prefix, value, suffix, name = name));
+            }
+            None => {
+                let operator = self.transpile_binary_operator(operator);
+                self.result.push_str(&format!("; {} {}= {}; }})", name, operator, value));
+            }
+        }
     }
 
     fn visit_binary_expression(
         &mut self,
         ast: &mut Ast,
         binary_expression: &BinaryExpr,
-        _expr: &Expr,
+        expr: &Expr,
     ) {
-        self.visit_expression(ast, binary_expression.left);
+        let operator = &binary_expression.operator.kind;
+        let operand_type = &ast.query_expr(binary_expression.left).ty;
+        if let Some((prefix, suffix)) = self.binary_operator_runtime_call(operator, operand_type, &expr.span(ast)) {
+            self.result.push_str(&prefix);
+            self.visit_expression(ast, binary_expression.left);
+            self.result.push_str(", ");
+            self.visit_expression(ast, binary_expression.right);
+            self.result.push_str(&suffix);
+            return;
+        }
+        let left_needs_parentheses = Self::binary_operand_needs_parentheses(ast, binary_expression.left, operator, OperandSide::Left);
+        self.visit_operand(ast, binary_expression.left, left_needs_parentheses);
         self.write_whitespace();
         self.result
             .push_str(self.transpile_binary_operator(&binary_expression.operator));
         self.write_whitespace();
-        self.visit_expression(ast, binary_expression.right);
+        let right_needs_parentheses = Self::binary_operand_needs_parentheses(ast, binary_expression.right, operator, OperandSide::Right);
+        self.visit_operand(ast, binary_expression.right, right_needs_parentheses);
     }
 
     fn visit_parenthesized_expression(
@@ -286,13 +868,12 @@ impl ASTVisitor for CTranspiler<'_> {
     }
 
     fn visit_block_expr(&mut self, ast: &mut Ast, block_expr: &BlockExpr, expr: &Expr) {
-        for statement in block_expr.stmts.iter().take(block_expr.stmts.len() - 1) {
+        for statement in block_expr.stmts.iter().take(block_expr.stmts.len().saturating_sub(1)) {
             self.visit_statement(ast, *statement);
         }
         if let Some((assign_to, r_value_id)) = self.l_value_stack.last() {
             if *r_value_id == expr.id {
-                self.result
-                    .push_str(&self.global_scope.variables.get(*assign_to).name);
+                self.result.push_str(&self.variable_name(*assign_to));
                 self.result.push_str(" = ");
             }
         }
@@ -301,3 +882,268 @@ impl ASTVisitor for CTranspiler<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::process::Command;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::ast::evaluator::Value;
+    use crate::codegen::CTranspiler;
+    use crate::compilation_unit::CompilationUnit;
+
+    fn transpile(input: &str) -> String {
+        let mut compilation_unit = CompilationUnit::compile(input).expect("Failed to compile");
+        CTranspiler::new(&compilation_unit.global_scope, &compilation_unit.text).transpile(&mut compilation_unit.ast)
+    }
+
+    /// Compiles the transpiled `input` with `cc`, runs it and returns what it printed to
+    /// stderr, or `None` if there is no C compiler to run it with.
+    fn run(input: &str) -> Option<String> {
+        static PROGRAMS: AtomicUsize = AtomicUsize::new(0);
+        if Command::new("cc").arg("--version").output().is_err() {
+            eprintln!("Skipping, no C compiler found");
+            return None;
+        }
+        let path = std::env::temp_dir().join(format!("fusion-test-{}-{}", std::process::id(), PROGRAMS.fetch_add(1, Ordering::Relaxed)));
+        let source = path.with_extension("c");
+        std::fs::write(&source, transpile(input)).unwrap();
+        let compiled = Command::new("cc").arg(&source).arg("-o").arg(&path).output().unwrap();
+        assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+        let output = Command::new(&path).output().unwrap();
+        let _ = std::fs::remove_file(&source);
+        let _ = std::fs::remove_file(&path);
+        Some(String::from_utf8(output.stderr).unwrap())
+    }
+
+    /// Asserts that the string `result` evaluates to `expected` after `program`, in the
+    /// evaluator and in C. Programs have no output, so the C one reports its result as a
+    /// string `parse_int` fails on.
+    fn assert_backends_agree(program: &str, result: &str, expected: &str) {
+        let mut compilation_unit = CompilationUnit::compile(&format!("{}\n{}", program, result)).expect("Failed to compile");
+        let value = compilation_unit.evaluate().expect("Failed to evaluate");
+        assert_eq!(value, Some(Value::String(Arc::new(expected.to_string()))));
+        let Some(stderr) = run(&format!("{}\nparse_int(({}) + \"!\")", program, result)) else {
+            return;
+        };
+        let output = stderr.strip_prefix("Cannot parse '").and_then(|stderr| stderr.rsplit_once("!' as int"));
+        assert_eq!(output.map(|(output, _)| output), Some(expected), "{}", stderr);
+    }
+
+    /// Asserts that `input` fails with the runtime error `message` in the evaluator and in C.
+    fn assert_backends_fail_with(input: &str, message: &str) {
+        let mut compilation_unit = CompilationUnit::compile(input).expect("Failed to compile");
+        let error = compilation_unit.evaluate().expect_err("Expected a runtime error");
+        assert_eq!(error.message, message);
+        let Some(stderr) = run(input) else {
+            return;
+        };
+        assert!(stderr.starts_with(&format!("{} (", message)), "{}", stderr);
+    }
+
+    #[test]
+    pub fn should_declare_globals_at_file_scope() {
+        let input = "\
+        let g = 5
+        func f() -> int {
+            return g
+        }
+        let y = f()
+        let y = \"redeclared\"
+        let index = 1
+        ";
+        let transpiled = transpile(input);
+        let declarations = "int64_t fx_global_g_0;\nint64_t fx_global_y_1;\nchar* fx_global_y_2;\nint64_t fx_global_index_3;\nint64_t f();";
+        assert!(transpiled.contains(declarations), "{}", transpiled);
+        assert!(transpiled.contains("  fx_global_g_0 = 5;\n"), "{}", transpiled);
+        assert_eq!(run(input).unwrap_or_default(), "");
+    }
+
+    #[test]
+    pub fn should_print_floats_like_the_evaluator() {
+        let input = "\
+        let big = parse_float(\"1e20\")
+        let small = parse_float(\"1e-7\")
+        let zero = 0.0
+        let nan = zero / zero
+        let inf = 1.0 / zero
+        let sum = 0.1 + 0.2
+        ";
+        let result = "\"${big} ${small} ${nan} ${inf} ${-inf} ${sum} ${-1.5} ${-zero} ${2.0} ${123.456}\"";
+        assert_backends_agree(input, result, "100000000000000000000 0.0000001 NaN inf -inf 0.30000000000000004 -1.5 -0 2 123.456");
+    }
+
+    #[test]
+    pub fn should_transpile_remainders_and_shifts_to_runtime_calls() {
+        let transpiled = transpile("let a = -7\nlet b = a % 3\nlet c = a << 65\nlet d = a >> 66\nlet e = a >>> 127\nlet f = 200u8 << 9");
+        assert!(transpiled.contains("fx_global_b_1 = (int64_t) fx_mod(fx_global_a_0, 3, \"9:2\");"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_c_2 = (int64_t) fx_shl(fx_global_a_0, 65, 64);"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_d_3 = (int64_t) fx_shr(fx_global_a_0, 66, 64);"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_e_4 = (int64_t) fx_ushr(fx_global_a_0, 127, 64);"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_f_5 = (uint8_t) fx_shl(200U, 9, 8);"), "{}", transpiled);
+    }
+
+    #[test]
+    pub fn should_evaluate_remainders_shifts_and_bitwise_operators_like_the_evaluator() {
+        let input = "\
+        let a = -7
+        let min = -9223372036854775808
+        let minus_one = -1
+        let remainders = \"${a % 3} ${7 % -3} ${a % -3} ${min % minus_one}\"
+        let shifts = \"${1 << 64} ${1 << 65} ${-8 >> 66} ${1 << -1} ${-1 >>> 127} ${1u8 << 9} ${128u8 >> 15i64} ${-128i8 >> 8}\"
+        let bitwise = \"${a & 12 | 3 ^ ~a} ${~0u16} ${5i8 ^ -1i8}\"
+        ";
+        assert_backends_agree(
+            input,
+            "remainders + \" \" + shifts + \" \" + bitwise",
+            "-1 1 -1 0 1 2 -2 -9223372036854775808 1 2 1 -128 13 65535 -6",
+        );
+    }
+
+    #[test]
+    pub fn should_report_remainders_by_zero() {
+        assert_backends_fail_with("let zero = 0\nlet a = 7 % zero", "Remainder by zero");
+        assert_backends_fail_with("let zero = 0u8\nlet a = 7u8 % zero", "Remainder by zero");
+    }
+
+    #[test]
+    pub fn should_compute_compound_assignment_values_before_reading_the_target() {
+        let transpiled = transpile("let y = 3\ny ^= (y = 6)");
+        assert!(transpiled.contains("({ int64_t fx_value_4 = (fx_global_y_0 = 6); fx_global_y_0 ^= fx_value_4; });"), "{}", transpiled);
+        let input = "\
+        let y = 3
+        y ^= (y = 6)
+        let x = 5
+        x += (x = 10) * 2
+        let s = \"a\"
+        s += (s = \"b\")
+        let u = 250u8
+        u += 10u8
+        let k = 1
+        k <<= 65u8
+        ";
+        assert_backends_agree(input, "\"${y} ${x} ${s} ${u} ${k}\"", "0 30 bb 4 2");
+    }
+
+    #[test]
+    pub fn should_transpile_sized_integers_to_stdint_types() {
+        let input = "\
+        let a: u8 = 200u8
+        let b = a + 100u8
+        let c = 300 as u8
+        let d = -9223372036854775808
+        let e = 18446744073709551615u64
+        let f = -128i8 * 1i8
+        ";
+        let transpiled = transpile(input);
+        let declarations = "uint8_t fx_global_a_0;\nuint8_t fx_global_b_1;\nuint8_t fx_global_c_2;\nint64_t fx_global_d_3;\nuint64_t fx_global_e_4;\nint8_t fx_global_f_5;";
+        assert!(transpiled.contains(declarations), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_b_1 = (uint8_t) fx_wrapping_add(fx_global_a_0, 100U);"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_c_2 = (uint8_t) (300);"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_d_3 = INT64_MIN;"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_e_4 = 18446744073709551615ULL;"), "{}", transpiled);
+        assert!(transpiled.contains("fx_global_f_5 = (int8_t) fx_mul(-128, 1, INT8_MIN, INT8_MAX, \"17:6\");"), "{}", transpiled);
+    }
+
+    #[test]
+    pub fn should_wrap_and_truncate_sized_integers_like_the_evaluator() {
+        let input = "\
+        let a = 200u8 + 100u8
+        let b = 100u8 - 200u8
+        let c = 65535u16 * 65535u16
+        let d = 18446744073709551615u64 + 1u64
+        let e = ~0u8
+        let f = -1i8 >>> 1
+        let g = 1i8 << 7
+        let h = 300 as u8
+        let i = -1 as u32
+        let j = 255u8 as i8
+        let k = (-2i8) ** 7i8
+        let l = -9223372036854775808
+        let m = 3usize ** 40usize
+        ";
+        assert_backends_agree(
+            input,
+            "\"${a} ${b} ${c} ${d} ${e} ${f} ${g} ${h} ${i} ${j} ${k} ${l} ${m}\"",
+            "44 156 1 0 255 127 -128 44 4294967295 -1 -128 -9223372036854775808 12157665459056928801",
+        );
+    }
+
+    #[test]
+    pub fn should_report_signed_overflow_like_the_evaluator() {
+        assert_backends_fail_with("let a = 100i8\nlet b = a + a", "Integer overflow, the result does not fit into 'i8'");
+        assert_backends_fail_with("let a = 9223372036854775807\nlet b = a * 2", "Integer overflow, the result does not fit into 'int'");
+        assert_backends_fail_with("let a = -128i8\nlet b = -a", "Integer overflow, the result does not fit into 'i8'");
+        assert_backends_fail_with("let a = 120i8\na += 10i8", "Integer overflow, the result does not fit into 'i8'");
+        assert_backends_fail_with("let a = 2i16\nlet b = a ** 15i16", "Integer overflow, the result does not fit into 'i16'");
+        assert_backends_fail_with("let a = 0u32\nlet b = 1u32 / a", "Division by zero");
+        assert_backends_fail_with("let a = 2\nlet b = a ** -1", "Cannot raise an integer to the negative power -1");
+    }
+
+    #[test]
+    pub fn should_saturate_casts_from_floats_and_chars_like_the_evaluator() {
+        let input = "\
+        let nan = 0.0 / 0.0
+        let a = 300.7 as u8
+        let b = -5.9 as u8
+        let c = -200.5 as i8
+        let d = 1e30 as u64
+        let e = nan as i16
+        let f = 'é' as u8
+        let g = '€' as u8
+        let h = '€' as i8
+        let i = 233u8 as char
+        let j = 18446744073709551615u64 as char
+        let k = -1i8 as char
+        ";
+        assert_backends_agree(
+            input,
+            "\"${a} ${b} ${c} ${d} ${e} ${f} ${g} ${h} ${i} ${j} ${k}\"",
+            "255 0 -128 18446744073709551615 0 233 255 127 é \u{FFFD} \u{FFFD}",
+        );
+    }
+
+    #[test]
+    pub fn should_end_ranges_instead_of_overflowing() {
+        let transpiled = transpile("let n = 0\nfor i in 0..=10 step 2 { n += i }");
+        assert!(transpiled.contains("!fx_for_done_1 && i <= fx_for_end_1; fx_for_done_1 = __builtin_add_overflow(i, fx_for_step_1, &i)"), "{}", transpiled);
+        let input = "\
+        let n = 0
+        for i in 9223372036854775800..=9223372036854775807 { n += 1 }
+        let m = 0
+        for i in 9223372036854775800..9223372036854775807 step 5 { m += 1 }
+        ";
+        assert_backends_agree(input, "\"${n} ${m}\"", "8 2");
+    }
+
+    #[test]
+    pub fn should_index_slice_and_bound_ranges_with_any_integer_type() {
+        let transpiled = transpile("let n = 0\nfor j in 0u8..3u8 { n += 1 }");
+        assert!(transpiled.contains("for (uint8_t j = 0U, fx_for_end_1 = 3U,"), "{}", transpiled);
+        let input = "\
+        let word = \"héllo\"
+        let i = 1usize
+        let n = 0
+        for j in 250u8..=255u8 { n += 1 }
+        let m = 0
+        for k in 0u8..7u8 step 2u8 { m += 1 }
+        ";
+        assert_backends_agree(
+            input,
+            "\"${word[i]} ${word[0usize..=2usize]} ${word[1u8..3u8]} ${n} ${m}\"",
+            "é hél él 6 4",
+        );
+        assert_backends_fail_with(
+            "let i = 18446744073709551615u64\nlet c = \"abc\"[i]",
+            "Index 18446744073709551615 is out of bounds for a string of length 3",
+        );
+        assert_backends_fail_with("let s = 0u8\nfor i in 0u8..3u8 step s {}", "For loop step must be positive, found 0");
+    }
+
+    #[test]
+    pub fn should_report_non_positive_steps() {
+        assert_backends_fail_with("let s = 0\nfor i in 0..10 step s {}", "For loop step must be positive, found 0");
+        assert_backends_fail_with("let s = -2\nfor i in 10..0 step s {}", "For loop step must be positive, found -2");
+    }
+}
@@ -0,0 +1,456 @@
+#include <ctype.h>
+#include <errno.h>
+#include <limits.h>
+#include <math.h>
+#include <stdarg.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Runtime support for transpiled fusion programs. Strings are immutable and
+ * never freed. */
+
+static char* fx_string_concat(const char* left, const char* right) {
+    size_t left_length = strlen(left);
+    size_t right_length = strlen(right);
+    char* result = malloc(left_length + right_length + 1);
+    memcpy(result, left, left_length);
+    memcpy(result + left_length, right, right_length + 1);
+    return result;
+}
+
+/* Prints the error like the evaluator does, at the "<column>:<line>" location
+ * of the expression that failed, and exits. */
+static void fx_runtime_error(const char* location, const char* format, ...) {
+    va_list arguments;
+    va_start(arguments, format);
+    vfprintf(stderr, format, arguments);
+    va_end(arguments);
+    fprintf(stderr, " (%s)\n", location);
+    exit(1);
+}
+
+/* Strings are UTF-8 and indexed by character. Continuation bytes look like
+ * 10xxxxxx, every other byte starts a character. */
+
+static int fx_is_char_start(char byte) {
+    return ((unsigned char) byte & 0xC0) != 0x80;
+}
+
+static long long fx_string_length(const char* string) {
+    long long length = 0;
+    for (; *string != '\0'; string++) {
+        length += fx_is_char_start(*string);
+    }
+    return length;
+}
+
+/* The start of the character at `index`, the end of the string if `index` is
+ * its length, NULL if it is out of bounds. */
+static const char* fx_char_boundary(const char* string, long long index) {
+    if (index < 0) {
+        return NULL;
+    }
+    for (; *string != '\0'; string++) {
+        if (fx_is_char_start(*string) && index-- == 0) {
+            return string;
+        }
+    }
+    return index == 0 ? string : NULL;
+}
+
+static char* fx_substring(const char* start, const char* end) {
+    char* result = malloc(end - start + 1);
+    memcpy(result, start, end - start);
+    result[end - start] = '\0';
+    return result;
+}
+
+/* Decodes the character starting at `at` and points `next` past it. Chars are
+ * code points, stored as uint32_t. */
+static uint32_t fx_decode_char(const char* at, const char** next) {
+    unsigned char byte = (unsigned char) *at;
+    int continuation_bytes = byte < 0x80 ? 0 : byte < 0xE0 ? 1 : byte < 0xF0 ? 2 : 3;
+    uint32_t code_point = continuation_bytes == 0 ? byte : byte & (0x3F >> continuation_bytes);
+    for (at++; continuation_bytes > 0; continuation_bytes--, at++) {
+        code_point = (code_point << 6) | ((unsigned char) *at & 0x3F);
+    }
+    *next = at;
+    return code_point;
+}
+
+static char* fx_char_to_string(uint32_t code_point) {
+    char* result = malloc(5);
+    int length;
+    if (code_point < 0x80) {
+        result[0] = code_point;
+        length = 1;
+    } else if (code_point < 0x800) {
+        result[0] = 0xC0 | (code_point >> 6);
+        length = 2;
+    } else if (code_point < 0x10000) {
+        result[0] = 0xE0 | (code_point >> 12);
+        length = 3;
+    } else {
+        result[0] = 0xF0 | (code_point >> 18);
+        length = 4;
+    }
+    for (int i = 1; i < length; i++) {
+        result[i] = 0x80 | ((code_point >> (6 * (length - 1 - i))) & 0x3F);
+    }
+    result[length] = '\0';
+    return result;
+}
+
+/* Indices may have any integer type. Those of unsigned 64 bit types above
+ * LLONG_MAX arrive negative, they are out of bounds but printed as unsigned. */
+static void fx_format_index(char* buffer, long long index, int is_unsigned) {
+    snprintf(buffer, 24, is_unsigned ? "%llu" : "%lld", index);
+}
+
+static uint32_t fx_string_index(const char* string, long long index, int is_unsigned, const char* location) {
+    const char* start = fx_char_boundary(string, index);
+    if (start == NULL || *start == '\0') {
+        char formatted[24];
+        fx_format_index(formatted, index, is_unsigned);
+        fx_runtime_error(location, "Index %s is out of bounds for a string of length %lld", formatted, fx_string_length(string));
+    }
+    const char* next;
+    return fx_decode_char(start, &next);
+}
+
+static char* fx_string_slice(const char* string, long long start, long long end, int inclusive, int is_unsigned, const char* location) {
+    const char* range_operator = inclusive ? "..=" : "..";
+    char formatted_start[24], formatted_end[24];
+    fx_format_index(formatted_start, start, is_unsigned);
+    fx_format_index(formatted_end, end, is_unsigned);
+    int starts_after_end = is_unsigned
+        ? (unsigned long long) start > (unsigned long long) end + inclusive && (unsigned long long) end + inclusive != 0
+        : start > end + inclusive && !(inclusive && end == LLONG_MAX);
+    /* No string is long enough to include the character at LLONG_MAX */
+    int end_overflows = (is_unsigned && end < 0) || (inclusive && end == LLONG_MAX);
+    long long exclusive_end = inclusive && !end_overflows ? end + 1 : end;
+    if (starts_after_end) {
+        fx_runtime_error(location, "Slice %s%s%s starts after its end", formatted_start, range_operator, formatted_end);
+    }
+    const char* start_char = fx_char_boundary(string, start);
+    const char* end_char = end_overflows ? NULL : fx_char_boundary(string, exclusive_end);
+    if (start_char == NULL || end_char == NULL) {
+        fx_runtime_error(location, "Slice %s%s%s is out of bounds for a string of length %lld",
+                         formatted_start, range_operator, formatted_end, fx_string_length(string));
+    }
+    return fx_substring(start_char, end_char);
+}
+
+/* strcmp compares bytes as unsigned char, which for UTF-8 is the order of the
+ * code points, like the evaluator. */
+static int fx_string_equals(const char* left, const char* right) {
+    return strcmp(left, right) == 0;
+}
+
+static int fx_string_not_equals(const char* left, const char* right) {
+    return strcmp(left, right) != 0;
+}
+
+static int fx_string_less_than(const char* left, const char* right) {
+    return strcmp(left, right) < 0;
+}
+
+static int fx_string_less_than_or_equal(const char* left, const char* right) {
+    return strcmp(left, right) <= 0;
+}
+
+static int fx_string_greater_than(const char* left, const char* right) {
+    return strcmp(left, right) > 0;
+}
+
+static int fx_string_greater_than_or_equal(const char* left, const char* right) {
+    return strcmp(left, right) >= 0;
+}
+
+static char* fx_int_to_string(long long value) {
+    char* result = malloc(21);
+    snprintf(result, 21, "%lld", value);
+    return result;
+}
+
+static char* fx_uint_to_string(unsigned long long value) {
+    char* result = malloc(21);
+    snprintf(result, 21, "%llu", value);
+    return result;
+}
+
+/* Prints the shortest digits that read back to the same value, without an
+ * exponent and with NaN and the infinities spelled "NaN", "inf" and "-inf",
+ * which is how the evaluator prints floats as well. */
+static char* fx_float_to_string(double value) {
+    if (isnan(value)) {
+        return "NaN";
+    }
+    if (isinf(value)) {
+        return value > 0 ? "inf" : "-inf";
+    }
+    char scientific[32];
+    for (int precision = 0; precision <= 17; precision++) {
+        snprintf(scientific, sizeof scientific, "%.*e", precision, value);
+        if (strtod(scientific, NULL) == value) {
+            break;
+        }
+    }
+    /* scientific is "[-]d.ddde[+-]xx", the value is 0.ddd times 10 to the point */
+    const char* at = scientific;
+    int negative = *at == '-';
+    at += negative;
+    char digits[20];
+    int count = 0;
+    for (; *at != 'e'; at++) {
+        if (*at != '.') {
+            digits[count++] = *at;
+        }
+    }
+    while (count > 1 && digits[count - 1] == '0') {
+        count--;
+    }
+    int point = atoi(at + 1) + 1;
+    char* result = malloc(count + abs(point) + 4);
+    char* out = result;
+    if (negative) {
+        *out++ = '-';
+    }
+    if (point <= 0) {
+        *out++ = '0';
+        *out++ = '.';
+        for (int i = point; i < 0; i++) {
+            *out++ = '0';
+        }
+    }
+    for (int i = 0; i < count || i < point; i++) {
+        if (i == point && point > 0) {
+            *out++ = '.';
+        }
+        *out++ = i < count ? digits[i] : '0';
+    }
+    *out = '\0';
+    return result;
+}
+
+static char* fx_bool_to_string(int value) {
+    return value ? "true" : "false";
+}
+
+/* Conversions, with the semantics of the evaluator. */
+
+/* Values that are not code points, surrogates included, become U+FFFD. */
+static uint32_t fx_int_to_char(long long value) {
+    if (value < 0 || value > 0x10FFFF || (value >= 0xD800 && value <= 0xDFFF)) {
+        return 0xFFFD;
+    }
+    return value;
+}
+
+/* Truncates toward zero, saturates at the bounds and maps NaN to 0, where a C
+ * cast would be undefined. The maximum of int64_t rounds up to 2^63 as a double,
+ * which is why the comparison includes it. */
+static long long fx_float_to_int(double value, long long min, long long max) {
+    if (value != value) {
+        return 0;
+    }
+    if (value >= (double) max) {
+        return max;
+    }
+    if (value <= (double) min) {
+        return min;
+    }
+    return (long long) value;
+}
+
+static unsigned long long fx_float_to_uint(double value, unsigned long long max) {
+    if (value != value || value <= 0.0) {
+        return 0;
+    }
+    if (value >= (double) max) {
+        return max;
+    }
+    return (unsigned long long) value;
+}
+
+static unsigned long long fx_char_to_integer(uint32_t value, unsigned long long max) {
+    return value > max ? max : value;
+}
+
+/* strtoll and strtod accept more than the evaluator does, leading whitespace,
+ * hexadecimal floats and "nan(...)" are rejected here. */
+static int fx_is_parseable(const char* string, const char* end) {
+    return *string != '\0' && !isspace((unsigned char) *string) && *end == '\0'
+        && strpbrk(string, "xX(") == NULL;
+}
+
+static long long fx_parse_int(const char* string, const char* location) {
+    char* end;
+    errno = 0;
+    long long value = strtoll(string, &end, 10);
+    if (!fx_is_parseable(string, end) || errno == ERANGE) {
+        fx_runtime_error(location, "Cannot parse '%s' as int", string);
+    }
+    return value;
+}
+
+static double fx_parse_float(const char* string, const char* location) {
+    char* end;
+    double value = strtod(string, &end);
+    if (!fx_is_parseable(string, end)) {
+        fx_runtime_error(location, "Cannot parse '%s' as float", string);
+    }
+    return value;
+}
+
+/* Integer operators whose C counterparts are undefined for some operands or
+ * promote narrow ones to int. The evaluator implements the same semantics: signed
+ * arithmetic that overflows its type is an error, unsigned arithmetic wraps around.
+ * Operands are widened to 64 bits, the caller casts the result back to its type. */
+
+static const char* fx_signed_type_name(long long min) {
+    switch (min) {
+        case INT8_MIN: return "i8";
+        case INT16_MIN: return "i16";
+        case INT32_MIN: return "i32";
+        default: return "int";
+    }
+}
+
+/* Signed operations take the bounds of their type. */
+static long long fx_checked(long long value, int overflowed, long long min, long long max, const char* location) {
+    if (overflowed || value < min || value > max) {
+        fx_runtime_error(location, "Integer overflow, the result does not fit into '%s'", fx_signed_type_name(min));
+    }
+    return value;
+}
+
+static long long fx_add(long long left, long long right, long long min, long long max, const char* location) {
+    long long result;
+    int overflowed = __builtin_add_overflow(left, right, &result);
+    return fx_checked(result, overflowed, min, max, location);
+}
+
+static long long fx_sub(long long left, long long right, long long min, long long max, const char* location) {
+    long long result;
+    int overflowed = __builtin_sub_overflow(left, right, &result);
+    return fx_checked(result, overflowed, min, max, location);
+}
+
+static long long fx_mul(long long left, long long right, long long min, long long max, const char* location) {
+    long long result;
+    int overflowed = __builtin_mul_overflow(left, right, &result);
+    return fx_checked(result, overflowed, min, max, location);
+}
+
+static long long fx_div(long long left, long long right, long long min, long long max, const char* location) {
+    if (right == 0) {
+        fx_runtime_error(location, "Division by zero");
+    }
+    /* LLONG_MIN / -1 overflows */
+    int overflowed = left == LLONG_MIN && right == -1;
+    return fx_checked(overflowed ? 0 : left / right, overflowed, min, max, location);
+}
+
+static long long fx_mod(long long left, long long right, const char* location) {
+    if (right == 0) {
+        fx_runtime_error(location, "Remainder by zero");
+    }
+    /* LLONG_MIN % -1 overflows in C */
+    if (right == -1) {
+        return 0;
+    }
+    return left % right;
+}
+
+/* Exponentiation by squaring. The products are checked one at a time, and each is
+ * a factor of the result, so they overflow exactly when the result does. */
+static long long fx_pow(long long base, long long exponent, long long min, long long max, const char* location) {
+    if (exponent < 0) {
+        fx_runtime_error(location, "Cannot raise an integer to the negative power %lld", exponent);
+    }
+    long long result = 1;
+    while (exponent > 1) {
+        if (exponent & 1) {
+            result = fx_mul(result, base, min, max, location);
+        }
+        exponent /= 2;
+        base = fx_mul(base, base, min, max, location);
+    }
+    if (exponent == 1) {
+        result = fx_mul(result, base, min, max, location);
+    }
+    return result;
+}
+
+static unsigned long long fx_wrapping_add(unsigned long long left, unsigned long long right) {
+    return left + right;
+}
+
+static unsigned long long fx_wrapping_sub(unsigned long long left, unsigned long long right) {
+    return left - right;
+}
+
+static unsigned long long fx_wrapping_mul(unsigned long long left, unsigned long long right) {
+    return left * right;
+}
+
+static unsigned long long fx_unsigned_div(unsigned long long left, unsigned long long right, const char* location) {
+    if (right == 0) {
+        fx_runtime_error(location, "Division by zero");
+    }
+    return left / right;
+}
+
+static unsigned long long fx_unsigned_mod(unsigned long long left, unsigned long long right, const char* location) {
+    if (right == 0) {
+        fx_runtime_error(location, "Remainder by zero");
+    }
+    return left % right;
+}
+
+static unsigned long long fx_wrapping_pow(unsigned long long base, unsigned long long exponent) {
+    unsigned long long result = 1;
+    for (; exponent > 0; exponent /= 2) {
+        if (exponent & 1) {
+            result *= base;
+        }
+        base *= base;
+    }
+    return result;
+}
+
+/* Shift amounts are taken modulo the width of the type, which is `bits`. */
+static unsigned long long fx_shl(long long left, long long right, int bits) {
+    return (unsigned long long) left << (right & (bits - 1));
+}
+
+static long long fx_shr(long long left, long long right, int bits) {
+    int amount = (int) (right & (bits - 1));
+    /* Right-shifting a negative value is implementation-defined in C */
+    return left < 0 ? ~(~left >> amount) : left >> amount;
+}
+
+static unsigned long long fx_ushr(long long left, long long right, int bits) {
+    unsigned long long value = (unsigned long long) left;
+    if (bits < 64) {
+        value &= (1ULL << bits) - 1;
+    }
+    return value >> (right & (bits - 1));
+}
+
+static long long fx_for_step(long long step, const char* location) {
+    if (step <= 0) {
+        fx_runtime_error(location, "For loop step must be positive, found %lld", step);
+    }
+    return step;
+}
+
+static unsigned long long fx_unsigned_for_step(unsigned long long step, const char* location) {
+    if (step == 0) {
+        fx_runtime_error(location, "For loop step must be positive, found 0");
+    }
+    return step;
+}
@@ -1,14 +1,16 @@
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use fusion_compiler::{Idx, idx, IdxVec};
 
 use crate::{diagnostics, text};
-use crate::ast::{AssignExpr, Ast, BinaryExpr, BinOpKind, BlockExpr, BoolExpr, CallExpr, Expr, ExprId, FunctionDeclaration, IfExpr, ItemId, LetStmt, NumberExpr, ParenthesizedExpr, ReturnStmt, Stmt, StmtKind, UnaryExpr, UnOpKind, VarExpr, WhileStmt, StringExpr, DecimalExpr};
-use crate::ast::evaluator::ASTEvaluator;
+use crate::ast::{AssignExpr, Ast, BinaryExpr, BinOperator, BinOpKind, BlockExpr, BoolExpr, BreakStmt, CallExpr, CastExpr, CharExpr, ContinueStmt, Expr, ExprId, ExprKind, ForStmt, FunctionDeclaration, IfExpr, IndexExpr, ItemId, ItemKind, LetStmt, LoopExpr, LoopLabel, NumberExpr, ParenthesizedExpr, ReturnStmt, SliceExpr, Stmt, StmtKind, UnaryExpr, UnOperator, UnOpKind, VarExpr, WhileStmt, StringExpr, DecimalExpr, InterpolatedStringExpr, InterpolatedStringPart};
+use crate::ast::control_flow::ControlFlowChecker;
+use crate::ast::evaluator::{ASTEvaluator, Value};
 use crate::ast::lexer::{Lexer, Token};
 use crate::ast::parser::Parser;
 use crate::ast::visitor::ASTVisitor;
-use crate::diagnostics::DiagnosticsBagCell;
+use crate::diagnostics::{Diagnostic, DiagnosticsBagCell};
 use crate::diagnostics::printer::DiagnosticsPrinter;
 use crate::text::span::TextSpan;
 use crate::typings::Type;
@@ -24,6 +26,42 @@ pub struct Function {
     pub return_type: Type,
 }
 
+/// A function provided by the runtime instead of being declared in the program. A
+/// declared function with the same name shadows it.
+#[derive(Debug, Clone, Copy)]
+pub enum BuiltinFunction {
+    // Parses a decimal integer, aborting if the string is not one or is out of range
+    ParseInt,
+    // Parses a float, aborting if the string is not one
+    ParseFloat,
+    // The number of characters in a string
+    Len,
+}
+
+impl BuiltinFunction {
+    pub fn lookup(name: &str) -> Option<BuiltinFunction> {
+        match name {
+            "parse_int" => Some(BuiltinFunction::ParseInt),
+            "parse_float" => Some(BuiltinFunction::ParseFloat),
+            "len" => Some(BuiltinFunction::Len),
+            _ => None,
+        }
+    }
+
+    pub fn parameter_types(&self) -> Vec<Type> {
+        match self {
+            BuiltinFunction::ParseInt | BuiltinFunction::ParseFloat | BuiltinFunction::Len => vec![Type::String],
+        }
+    }
+
+    pub fn return_type(&self) -> Type {
+        match self {
+            BuiltinFunction::ParseInt | BuiltinFunction::Len => Type::Int,
+            BuiltinFunction::ParseFloat => Type::Float,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct VariableSymbol {
     pub name: String,
@@ -115,6 +153,15 @@ struct Scopes {
     global_scope: GlobalScope,
 }
 
+/// A loop that `break` and `continue` can refer to.
+struct LoopScope {
+    label: Option<String>,
+    // Only `loop` expressions can be exited with a value
+    is_loop_expression: bool,
+    // The type of the values the loop is exited with, once the first `break` is seen
+    break_type: Option<Type>,
+}
+
 impl Scopes {
     fn new() -> Self {
         Scopes {
@@ -123,12 +170,6 @@ impl Scopes {
         }
     }
 
-    fn from_global_scope(global_scope: GlobalScope) -> Self {
-        Scopes {
-            local_scopes: Vec::new(),
-            global_scope,
-        }
-    }
 
     fn enter_function_scope(&mut self, function_idx: FunctionIdx) {
         self._enter_scope(Some(function_idx));
@@ -198,9 +239,21 @@ impl Scopes {
     }
 }
 
+/// The globals and the functions that a function body or a top-level statement uses.
+#[derive(Default)]
+struct Uses {
+    globals: Vec<VariableIdx>,
+    functions: Vec<(FunctionIdx, TextSpan)>,
+}
+
 struct Resolver {
     scopes: Scopes,
     diagnostics: DiagnosticsBagCell,
+    // The loops around the statement being resolved, innermost last
+    loops: Vec<LoopScope>,
+    // What the function body or top-level statement being resolved uses
+    uses: Uses,
+    function_uses: HashMap<FunctionIdx, Uses>,
 }
 
 fn expect_type(diagnostics: &DiagnosticsBagCell, expected: Type, actual: &Type, span: &TextSpan) -> Type {
@@ -215,71 +268,296 @@ impl Resolver {
         Resolver {
             scopes,
             diagnostics,
+            loops: Vec::new(),
+            uses: Uses::default(),
+            function_uses: HashMap::new(),
         }
     }
 
 
+    /// Resolves in passes so that the order of the items doesn't matter: function signatures
+    /// are declared first, then the top-level statements declare the globals, and function
+    /// bodies are checked last, when every function and global is known.
     pub fn resolve(&mut self, ast: &mut Ast) {
-        for id in ast.items.cloned_indices() {
+        let item_ids = ast.items.cloned_indices();
+        for id in &item_ids {
+            self.declare_function(ast, *id);
+        }
+        let (functions, statements): (Vec<ItemId>, Vec<ItemId>) = item_ids.into_iter().partition(
+            |id| matches!(ast.query_item(*id).kind, ItemKind::Function(_))
+        );
+        let mut statement_uses = Vec::new();
+        for id in statements {
+            let declared = self.scopes.global_scope.global_variables.len();
             self.visit_item(ast, id);
+            let initialized = self.scopes.global_scope.global_variables[declared..].to_vec();
+            statement_uses.push((std::mem::take(&mut self.uses), initialized));
+        }
+        for id in functions {
+            self.visit_item(ast, id);
+        }
+        self.check_globals_initialized_before_calls(&statement_uses);
+    }
+
+    /// Functions may use globals declared after them, but the top-level statements must
+    /// not call them before those globals are initialized. `statements` are the uses of
+    /// each statement in order, along with the globals it initializes.
+    fn check_globals_initialized_before_calls(&self, statements: &[(Uses, Vec<VariableIdx>)]) {
+        let mut initialized = HashSet::new();
+        for (uses, globals) in statements {
+            for (function, span) in &uses.functions {
+                if let Some(global) = self.uninitialized_global_used_by(*function, &initialized) {
+                    self.diagnostics.borrow_mut().report_global_used_before_initialization(
+                        span,
+                        &self.scopes.global_scope.functions.get(*function).name,
+                        &self.scopes.global_scope.variables.get(global).name,
+                    );
+                }
+            }
+            initialized.extend(globals.iter().copied());
+        }
+    }
+
+    /// A global that is not `initialized` but used by `function` or by a function it calls.
+    fn uninitialized_global_used_by(&self, function: FunctionIdx, initialized: &HashSet<VariableIdx>) -> Option<VariableIdx> {
+        let mut visited = HashSet::new();
+        let mut pending = vec![function];
+        while let Some(function) = pending.pop() {
+            if !visited.insert(function) {
+                continue;
+            }
+            let Some(uses) = self.function_uses.get(&function) else {
+                continue;
+            };
+            if let Some(global) = uses.globals.iter().find(|global| !initialized.contains(*global)) {
+                return Some(*global);
+            }
+            pending.extend(uses.functions.iter().map(|(function, _)| *function));
+        }
+        None
+    }
+
+    fn record_variable_use(&mut self, variable: VariableIdx) {
+        if self.scopes.global_scope.global_variables.contains(&variable) {
+            self.uses.globals.push(variable);
         }
     }
 
+    fn declare_function(&mut self, ast: &mut Ast, item_id: ItemId) {
+        let func_decl = match &ast.query_item(item_id).kind {
+            ItemKind::Function(func_decl) => func_decl.clone(),
+            _ => return,
+        };
+        let parameter_types: Vec<Type> = func_decl.parameters.iter().map(
+            |parameter| resolve_type_from_string(&self.diagnostics, &parameter.type_annotation.type_name)
+        ).collect();
+        let return_type = func_decl.return_type.as_ref()
+            .map(|return_type| resolve_type_from_string(&self.diagnostics, &return_type.type_name))
+            .unwrap_or(Type::Void);
+        let function_idx = match self.scopes.global_scope.create_function(
+            func_decl.identifier.span.literal.clone(),
+            func_decl.body,
+            Vec::new(),
+            return_type,
+        ) {
+            Ok(created_function_idx) => created_function_idx,
+            Err(already_existing_function_idx) => {
+                self.diagnostics.borrow_mut().report_function_already_declared(&func_decl.identifier);
+                ast.set_function_for_item(item_id, already_existing_function_idx);
+                return;
+            }
+        };
+        // The parameters are locals of the function, `visit_func_decl` brings them into
+        // the scope of its body
+        self.scopes.enter_function_scope(function_idx);
+        let parameters = func_decl.parameters.iter().zip(parameter_types).map(
+            |(parameter, ty)| self.scopes.declare_variable(&parameter.identifier.span.literal, ty)
+        ).collect();
+        self.scopes.exit_function_scope();
+        self.scopes.global_scope.functions[function_idx].parameters = parameters;
+        ast.set_function_for_item(item_id, function_idx);
+    }
+
+    /// Picks the overload of `operator` matching the operand types and returns the operator
+    /// it resolves to along with the result type. Operands of an overloaded operator must
+    /// have the same type, an `int` is never promoted to a `float`.
     pub fn resolve_binary_expression(
         &self,
-        ast: &Ast,
-        left: &Expr,
-        right: &Expr,
-        operator: &BinOpKind,
-    ) -> Type {
-        let matrix: (Type, Type, Type) = match operator {
-            //                  left type, right type, result type
-            BinOpKind::Plus => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::Minus => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::Multiply => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::Divide => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::Power => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::PlusDecimal => (Type::Float, Type::Float, Type::Float),
-            BinOpKind::MinusDecimal => (Type::Float, Type::Float, Type::Float),
-            BinOpKind::MultiplyDecimal => (Type::Float, Type::Float, Type::Float),
-            BinOpKind::DivideDecimal => (Type::Float, Type::Float, Type::Float),
-            BinOpKind::PlusString => (Type::String, Type::String, Type::String),
-            BinOpKind::BitwiseAnd => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::BitwiseOr => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::BitwiseXor => (Type::Int, Type::Int, Type::Int),
-            BinOpKind::Equals => (Type::Int, Type::Int, Type::Bool),
-            BinOpKind::NotEquals => (Type::Int, Type::Int, Type::Bool),
-            BinOpKind::LessThan => (Type::Int, Type::Int, Type::Bool),
-            BinOpKind::LessThanOrEqual => (Type::Int, Type::Int, Type::Bool),
-            BinOpKind::GreaterThan => (Type::Int, Type::Int, Type::Bool),
-            BinOpKind::GreaterThanOrEqual => (Type::Int, Type::Int, Type::Bool),
+        operator: &BinOperator,
+        (left, left_span): (&Type, TextSpan),
+        (right, right_span): (&Type, TextSpan),
+    ) -> (BinOpKind, Type) {
+        let overloads = Self::binary_operator_overloads(&operator.kind);
+        let accepts_left = |overload: &&(Type, Type, Type, BinOpKind)| left.is_assignable_to(&overload.0);
+        let accepts_right = |overload: &&(Type, Type, Type, BinOpKind)| right.is_assignable_to(&overload.1);
+        if let Some(overload) = overloads.iter().find(|overload| accepts_left(overload) && accepts_right(overload)) {
+            return (overload.3.clone(), overload.2.clone());
+        }
+        if overloads.iter().any(|overload| accepts_left(&overload)) && overloads.iter().any(|overload| accepts_right(&overload)) {
+            self.diagnostics.borrow_mut().report_mismatched_operand_types(&operator.token, left, right);
+        } else {
+            let overload = overloads.iter().find(accepts_left)
+                .or_else(|| overloads.iter().find(accepts_right))
+                .unwrap_or(&overloads[0]);
+            self.expect_type(overload.0.clone(), left, &left_span);
+            self.expect_type(overload.1.clone(), right, &right_span);
+        }
+        let overload = &overloads[0];
+        (overload.3.clone(), overload.2.clone())
+    }
+
+    fn binary_operator_overloads(operator: &BinOpKind) -> Vec<(Type, Type, Type, BinOpKind)> {
+        // The integer operators keep their kind, the type of the operands selects the semantics
+        let integers = |operator: &BinOpKind, result: Option<Type>| Type::integers()
+            .map(|ty| (ty.clone(), ty.clone(), result.clone().unwrap_or(ty), operator.clone()))
+            .collect::<Vec<_>>();
+        let arithmetic = |int: BinOpKind, float: BinOpKind| {
+            let mut overloads = integers(&int, None);
+            overloads.push((Type::Float, Type::Float, Type::Float, float));
+            overloads
+        };
+        let comparison = |int: BinOpKind, float: BinOpKind, char: BinOpKind, string: BinOpKind| {
+            let mut overloads = integers(&int, Some(Type::Bool));
+            overloads.extend([
+                (Type::Float, Type::Float, Type::Bool, float),
+                (Type::Char, Type::Char, Type::Bool, char),
+                (Type::String, Type::String, Type::Bool, string),
+            ]);
+            overloads
         };
+        let equality = |operator: BinOpKind, string: BinOpKind| {
+            let mut overloads = integers(&operator, Some(Type::Bool));
+            overloads.extend([
+                (Type::Float, Type::Float, Type::Bool, operator.clone()),
+                (Type::Bool, Type::Bool, Type::Bool, operator.clone()),
+                (Type::Char, Type::Char, Type::Bool, operator),
+                (Type::String, Type::String, Type::Bool, string),
+            ]);
+            overloads
+        };
+        // The shift amount may have any integer type
+        let shift = |operator: &BinOpKind| Type::integers()
+            .flat_map(|ty| Type::integers().map(move |amount| (ty.clone(), amount, ty.clone(), operator.clone())))
+            .collect::<Vec<_>>();
+        match operator {
+            //                   left type, right type, result type, resolved operator
+            BinOpKind::Plus => {
+                let mut overloads = arithmetic(BinOpKind::Plus, BinOpKind::PlusDecimal);
+                overloads.push((Type::String, Type::String, Type::String, BinOpKind::PlusString));
+                overloads
+            }
+            BinOpKind::Minus => arithmetic(BinOpKind::Minus, BinOpKind::MinusDecimal),
+            BinOpKind::Multiply => arithmetic(BinOpKind::Multiply, BinOpKind::MultiplyDecimal),
+            BinOpKind::Divide => arithmetic(BinOpKind::Divide, BinOpKind::DivideDecimal),
+            BinOpKind::Modulo | BinOpKind::Power | BinOpKind::BitwiseAnd | BinOpKind::BitwiseOr
+            | BinOpKind::BitwiseXor => integers(operator, None),
+            BinOpKind::ShiftLeft | BinOpKind::ShiftRight | BinOpKind::UnsignedShiftRight => shift(operator),
+            BinOpKind::Equals => equality(BinOpKind::Equals, BinOpKind::EqualsString),
+            BinOpKind::NotEquals => equality(BinOpKind::NotEquals, BinOpKind::NotEqualsString),
+            BinOpKind::LessThan => comparison(BinOpKind::LessThan, BinOpKind::LessThanDecimal, BinOpKind::LessThanChar, BinOpKind::LessThanString),
+            BinOpKind::LessThanOrEqual => comparison(BinOpKind::LessThanOrEqual, BinOpKind::LessThanOrEqualDecimal, BinOpKind::LessThanOrEqualChar, BinOpKind::LessThanOrEqualString),
+            BinOpKind::GreaterThan => comparison(BinOpKind::GreaterThan, BinOpKind::GreaterThanDecimal, BinOpKind::GreaterThanChar, BinOpKind::GreaterThanString),
+            BinOpKind::GreaterThanOrEqual => comparison(BinOpKind::GreaterThanOrEqual, BinOpKind::GreaterThanOrEqualDecimal, BinOpKind::GreaterThanOrEqualChar, BinOpKind::GreaterThanOrEqualString),
+            BinOpKind::LogicalAnd | BinOpKind::LogicalOr => vec![(Type::Bool, Type::Bool, Type::Bool, operator.clone())],
+            // Already resolved
+            BinOpKind::PlusDecimal | BinOpKind::MinusDecimal | BinOpKind::MultiplyDecimal | BinOpKind::DivideDecimal => {
+                vec![(Type::Float, Type::Float, Type::Float, operator.clone())]
+            }
+            BinOpKind::LessThanDecimal | BinOpKind::LessThanOrEqualDecimal | BinOpKind::GreaterThanDecimal
+            | BinOpKind::GreaterThanOrEqualDecimal => vec![(Type::Float, Type::Float, Type::Bool, operator.clone())],
+            BinOpKind::LessThanChar | BinOpKind::LessThanOrEqualChar | BinOpKind::GreaterThanChar
+            | BinOpKind::GreaterThanOrEqualChar => vec![(Type::Char, Type::Char, Type::Bool, operator.clone())],
+            BinOpKind::PlusString => vec![(Type::String, Type::String, Type::String, operator.clone())],
+            BinOpKind::EqualsString | BinOpKind::NotEqualsString | BinOpKind::LessThanString | BinOpKind::LessThanOrEqualString
+            | BinOpKind::GreaterThanString | BinOpKind::GreaterThanOrEqualString => {
+                vec![(Type::String, Type::String, Type::Bool, operator.clone())]
+            }
+        }
+    }
 
-        self.expect_type(matrix.0, &left.ty, &left.span(&ast));
+    fn expect_type(&self, expected: Type, actual: &Type, span: &TextSpan) -> Type {
+        expect_type(&self.diagnostics, expected, actual, span)
+    }
 
-        self.expect_type(matrix.1, &right.ty, &right.span(&ast));
+    /// The type of `expr` if it is an integer type, any of which can index strings and
+    /// bound ranges, otherwise reports that an `int` was expected.
+    fn expect_integer_type(&self, ast: &Ast, expr: ExprId) -> Type {
+        let expr = ast.query_expr(expr);
+        match expr.ty.integer_type() {
+            Some(_) => expr.ty.clone(),
+            None => self.expect_type(Type::Int, &expr.ty, &expr.span(ast)),
+        }
+    }
 
-        matrix.2
+    fn resolve_loop_body(&mut self, ast: &mut Ast, label: &Option<LoopLabel>, is_loop_expression: bool, body: ExprId) -> Option<Type> {
+        self.loops.push(LoopScope {
+            label: label.as_ref().map(|label| label.name().to_string()),
+            is_loop_expression,
+            break_type: None,
+        });
+        self.visit_expression(ast, body);
+        self.loops.pop().and_then(|loop_scope| loop_scope.break_type)
     }
 
-    fn expect_type(&self, expected: Type, actual: &Type, span: &TextSpan) -> Type {
-        expect_type(&self.diagnostics, expected, actual, span)
+    /// The index in `loops` of the loop that `keyword` (a `break` or `continue`) exits,
+    /// the innermost one unless a label is given.
+    fn resolve_loop_target(&self, keyword: &Token, label: &Option<Token>) -> Option<usize> {
+        let target = match label {
+            Some(label) => self.loops.iter().rposition(|loop_scope| loop_scope.label.as_deref() == Some(label.span.literal.as_str())),
+            None => self.loops.len().checked_sub(1),
+        };
+        if target.is_none() {
+            let mut diagnostics_binding = self.diagnostics.borrow_mut();
+            match label {
+                Some(label) => diagnostics_binding.report_unknown_label(label),
+                None => diagnostics_binding.report_cannot_use_outside_of_loop(keyword),
+            }
+        }
+        target
     }
 
 
-    pub fn resolve_unary_expression(&self, ast: &Ast, operand: &Expr, operator: &UnOpKind) -> Type {
-        let matrix: (Type, Type) = match operator {
-            UnOpKind::Minus => (Type::Int, Type::Int),
-            UnOpKind::BitwiseNot => (Type::Int, Type::Int),
+    /// Types an integer literal by its suffix and checks that it fits into the type. A
+    /// `negated` literal is the operand of a `-`, so it may go down to the minimum.
+    fn resolve_integer_literal(&self, ast: &mut Ast, number: &NumberExpr, expr: &Expr, negated: bool) {
+        let ty = number.ty();
+        let integer = ty.integer_type().expect("Literals have an integer type");
+        let value = match negated && integer.is_signed() {
+            true => -(number.number as i128),
+            false => number.number as i128,
+        };
+        if value < integer.min() || value > integer.max() {
+            self.diagnostics.borrow_mut().report_literal_out_of_range(&expr.span(ast), &ty);
+        }
+        ast.set_type(expr.id, ty);
+    }
+
+    pub fn resolve_unary_expression(&self, ast: &Ast, operand: &Expr, operator: &UnOperator) -> (UnOpKind, Type) {
+        let signed = operand.ty.integer_type().map(|integer| integer.is_signed());
+        let matrix: (Type, Type, UnOpKind) = match (&operator.kind, &operand.ty) {
+            (UnOpKind::Minus | UnOpKind::MinusDecimal, Type::Float) => (Type::Float, Type::Float, UnOpKind::MinusDecimal),
+            (UnOpKind::Minus | UnOpKind::MinusDecimal, ty) if signed == Some(true) => (ty.clone(), ty.clone(), UnOpKind::Minus),
+            (UnOpKind::Minus | UnOpKind::MinusDecimal, ty) if signed == Some(false) => {
+                self.diagnostics.borrow_mut().report_negated_unsigned_type(&operator.token, ty);
+                return (UnOpKind::Minus, ty.clone());
+            }
+            (UnOpKind::Minus | UnOpKind::MinusDecimal, _) => (Type::Int, Type::Int, UnOpKind::Minus),
+            (UnOpKind::BitwiseNot, ty @ (Type::Int | Type::Integer(_))) => (ty.clone(), ty.clone(), UnOpKind::BitwiseNot),
+            (UnOpKind::BitwiseNot, _) => (Type::Int, Type::Int, UnOpKind::BitwiseNot),
+            (UnOpKind::LogicalNot, _) => (Type::Bool, Type::Bool, UnOpKind::LogicalNot),
         };
 
         self.expect_type(matrix.0, &operand.ty, &operand.span(&ast));
 
-        matrix.1
+        (matrix.2, matrix.1)
     }
 }
 
 pub fn resolve_type_from_string(diagnostics: &DiagnosticsBagCell, type_name: &Token) -> Type {
+    if type_name.is_missing() {
+        // Already reported by the parser
+        return Type::Error;
+    }
     let ty = Type::from_str(&type_name.span.literal);
     let ty = match ty {
         None => {
@@ -300,7 +578,15 @@ impl ASTVisitor for Resolver {
         for parameter in function.parameters.clone() {
             self.scopes.current_local_scope_mut().locals.push(parameter);
         }
+        // Loops don't reach into the functions declared inside them
+        let loops = std::mem::take(&mut self.loops);
+        let uses = std::mem::take(&mut self.uses);
         self.visit_expression(ast, func_decl.body);
+        self.loops = loops;
+        let function_uses = std::mem::replace(&mut self.uses, uses);
+        self.function_uses.insert(function_idx, function_uses);
+        let return_type = self.scopes.global_scope.functions.get(function_idx).return_type.clone();
+        ControlFlowChecker::new(Rc::clone(&self.diagnostics)).check_function(ast, func_decl, &return_type);
         self.scopes.exit_function_scope();
     }
 
@@ -318,7 +604,7 @@ impl ASTVisitor for Resolver {
                     let return_expression = ast.query_expr(*return_expression);
                     self.expect_type(function.return_type.clone(), &return_expression.ty, &return_expression.span(&ast));
                 } else {
-                    self.expect_type(Type::Void, &function.return_type, &return_keyword.span);
+                    self.expect_type(function.return_type.clone(), &Type::Void, &return_keyword.span);
                 }
             }
         }
@@ -328,7 +614,74 @@ impl ASTVisitor for Resolver {
         self.visit_expression(ast, while_statement.condition);
         let condition = ast.query_expr(while_statement.condition);
         self.expect_type(Type::Bool, &condition.ty, &condition.span(&ast));
-        self.visit_expression(ast, while_statement.body);
+        self.resolve_loop_body(ast, &while_statement.label, false, while_statement.body);
+    }
+
+    fn visit_loop_expression(&mut self, ast: &mut Ast, loop_expr: &LoopExpr, expr: &Expr) {
+        let ty = self.resolve_loop_body(ast, &loop_expr.label, true, loop_expr.body);
+        ast.set_type(expr.id, ty.unwrap_or(Type::Void));
+    }
+
+    fn visit_break_statement(&mut self, ast: &mut Ast, break_statement: &BreakStmt) {
+        if let Some(value) = break_statement.value {
+            self.visit_expression(ast, value);
+        }
+        let Some(target) = self.resolve_loop_target(&break_statement.break_keyword, &break_statement.label) else {
+            return;
+        };
+        let (ty, span) = match break_statement.value {
+            Some(value) => {
+                let value = ast.query_expr(value);
+                (value.ty.clone(), value.span(ast))
+            }
+            None => (Type::Void, break_statement.break_keyword.span.clone()),
+        };
+        let loop_scope = &mut self.loops[target];
+        if !loop_scope.is_loop_expression {
+            if break_statement.value.is_some() {
+                self.diagnostics.borrow_mut().report_break_value_outside_of_loop_expression(&span);
+            }
+            return;
+        }
+        match loop_scope.break_type.clone() {
+            None => loop_scope.break_type = Some(ty),
+            Some(break_type) => {
+                self.expect_type(break_type, &ty, &span);
+            }
+        }
+    }
+
+    fn visit_continue_statement(&mut self, _ast: &mut Ast, continue_statement: &ContinueStmt) {
+        self.resolve_loop_target(&continue_statement.continue_keyword, &continue_statement.label);
+    }
+
+    fn visit_for_statement(&mut self, ast: &mut Ast, for_statement: &ForStmt, stmt: &Stmt) {
+        let variable_type = match &for_statement.range {
+            // The end and the step have the type of the start
+            Some(range) => {
+                let step = range.step.as_ref().map(|step| step.expr);
+                for bound in [Some(for_statement.start), Some(range.end), step].into_iter().flatten() {
+                    self.visit_expression(ast, bound);
+                }
+                let ty = self.expect_integer_type(ast, for_statement.start);
+                for bound in [Some(range.end), step].into_iter().flatten() {
+                    let bound = ast.query_expr(bound);
+                    self.expect_type(ty.clone(), &bound.ty, &bound.span(ast));
+                }
+                ty
+            }
+            None => {
+                self.visit_expression(ast, for_statement.start);
+                let string = ast.query_expr(for_statement.start);
+                self.expect_type(Type::String, &string.ty, &string.span(ast));
+                Type::Char
+            }
+        };
+        self.scopes.enter_scope();
+        let variable = self.scopes.declare_variable(&for_statement.identifier.span.literal, variable_type);
+        ast.set_variable_for_stmt(&stmt.id, variable);
+        self.resolve_loop_body(ast, &for_statement.label, false, for_statement.body);
+        self.scopes.exit_scope();
     }
 
     fn visit_block_expr(&mut self, ast: &mut Ast, block_expr: &BlockExpr, expr: &Expr) {
@@ -388,9 +741,20 @@ impl ASTVisitor for Resolver {
     }
 
     fn visit_call_expression(&mut self, ast: &mut Ast, call_expression: &CallExpr, expr: &Expr) {
-        let function  = self.scopes.global_scope.lookup_function(&call_expression.callee.span.literal);
+        let signature = match self.scopes.global_scope.lookup_function(&call_expression.callee.span.literal) {
+            Some(function) => {
+                self.uses.functions.push((function, call_expression.callee.span.clone()));
+                let function = self.scopes.global_scope.functions.get(function);
+                let parameter_types = function.parameters.iter()
+                    .map(|parameter| self.scopes.global_scope.variables.get(*parameter).ty.clone())
+                    .collect();
+                Some((parameter_types, function.return_type.clone()))
+            }
+            None => BuiltinFunction::lookup(&call_expression.callee.span.literal)
+                .map(|builtin| (builtin.parameter_types(), builtin.return_type())),
+        };
 
-        let ty = match function {
+        let ty = match signature {
             None => {
                 let mut diagnostics_binding = self.diagnostics.borrow_mut();
                 diagnostics_binding.report_undeclared_function(
@@ -398,23 +762,20 @@ impl ASTVisitor for Resolver {
                 );
                 Type::Error
             }
-            Some(function) => {
-                let function = self.scopes.global_scope.functions.get(function);
-                if function.parameters.len() != call_expression.arguments.len() {
+            Some((parameter_types, return_type)) => {
+                if parameter_types.len() != call_expression.arguments.len() {
                     let mut diagnostics_binding = self.diagnostics.borrow_mut();
                     diagnostics_binding.report_invalid_argument_count(
                         &call_expression.callee.span,
-                        function.parameters.len(),
+                        parameter_types.len(),
                         call_expression.arguments.len(),
                     );
                 }
-                let return_type = function.return_type.clone();
-                for (argument, param) in call_expression.arguments.iter().zip(function.parameters.clone().iter()) {
+                for (argument, parameter_type) in call_expression.arguments.iter().zip(parameter_types) {
                     self.visit_expression(ast, *argument);
                     let argument_expression = ast.query_expr(*argument);
-                    let param = self.scopes.global_scope.variables.get(*param);
                     self.expect_type(
-                        param.ty.clone(),
+                        parameter_type,
                         &argument_expression.ty,
                         &argument_expression.span(ast),
                     );
@@ -425,6 +786,37 @@ impl ASTVisitor for Resolver {
         ast.set_type(expr.id, ty);
     }
 
+    fn visit_cast_expression(&mut self, ast: &mut Ast, cast_expr: &CastExpr, expr: &Expr) {
+        self.visit_expression(ast, cast_expr.expr);
+        let operand = ast.query_expr(cast_expr.expr);
+        let ty = resolve_type_from_string(&self.diagnostics, &cast_expr.type_name);
+        if !operand.ty.is_castable_to(&ty) {
+            self.diagnostics.borrow_mut().report_invalid_cast(&expr.span(ast), &operand.ty, &ty);
+        }
+        ast.set_type(expr.id, ty);
+    }
+
+    fn visit_index_expression(&mut self, ast: &mut Ast, index_expr: &IndexExpr, expr: &Expr) {
+        self.visit_expression(ast, index_expr.target);
+        self.visit_expression(ast, index_expr.index);
+        let target = ast.query_expr(index_expr.target);
+        self.expect_type(Type::String, &target.ty, &target.span(ast));
+        self.expect_integer_type(ast, index_expr.index);
+        ast.set_type(expr.id, Type::Char);
+    }
+
+    fn visit_slice_expression(&mut self, ast: &mut Ast, slice_expr: &SliceExpr, expr: &Expr) {
+        self.visit_expression(ast, slice_expr.target);
+        self.visit_expression(ast, slice_expr.start);
+        self.visit_expression(ast, slice_expr.end);
+        let target = ast.query_expr(slice_expr.target);
+        self.expect_type(Type::String, &target.ty, &target.span(ast));
+        let ty = self.expect_integer_type(ast, slice_expr.start);
+        let end = ast.query_expr(slice_expr.end);
+        self.expect_type(ty, &end.ty, &end.span(ast));
+        ast.set_type(expr.id, Type::String);
+    }
+
     fn visit_assignment_expression(&mut self, ast: &mut Ast, assignment_expression: &AssignExpr, expr: &Expr) {
         self.visit_expression(ast, assignment_expression.expression);
         let identifier = assignment_expression.identifier.span.literal.clone();
@@ -436,9 +828,23 @@ impl ASTVisitor for Resolver {
             }
             Some(variable) => {
                 ast.set_variable(expr.id, variable);
+                self.record_variable_use(variable);
                 let variable = self.scopes.global_scope.variables.get(variable);
                 let value_expression = ast.query_expr(assignment_expression.expression);
-                self.expect_type(variable.ty.clone(), &value_expression.ty, &value_expression.span(&ast));
+                match &assignment_expression.operator {
+                    // Typed like `a = a <operator> value`
+                    Some(operator) => {
+                        let (operator, _) = self.resolve_binary_expression(
+                            operator,
+                            (&variable.ty, assignment_expression.identifier.span.clone()),
+                            (&value_expression.ty, value_expression.span(ast)),
+                        );
+                        ast.set_binary_operator(expr.id, operator);
+                    }
+                    None => {
+                        self.expect_type(variable.ty.clone(), &value_expression.ty, &value_expression.span(&ast));
+                    }
+                }
                 variable.ty.clone()
             }
         };
@@ -453,8 +859,10 @@ impl ASTVisitor for Resolver {
                 diagnostics_binding.report_undeclared_variable(
                     &variable_expression.identifier,
                 );
+                ast.set_type(expr.id, Type::Error);
             }
             Some(variable_idx) => {
+                self.record_variable_use(variable_idx);
                 let variable = self.scopes.global_scope.variables.get(variable_idx);
                 ast.set_type(expr.id, variable.ty.clone());
                 ast.set_variable(expr.id, variable_idx);
@@ -462,16 +870,32 @@ impl ASTVisitor for Resolver {
         };
     }
 
-    fn visit_number_expression(&mut self, ast: &mut Ast, _number: &NumberExpr, expr: &Expr) {
-        ast.set_type(expr.id, Type::Int);
+    fn visit_number_expression(&mut self, ast: &mut Ast, number: &NumberExpr, expr: &Expr) {
+        self.resolve_integer_literal(ast, number, expr, false);
     }
     fn visit_decimal_expression(&mut self, ast: &mut Ast, _number: &DecimalExpr, expr: &Expr) {
         ast.set_type(expr.id, Type::Float);
     }
+    fn visit_char_expression(&mut self, ast: &mut Ast, _character: &CharExpr, expr: &Expr) {
+        ast.set_type(expr.id, Type::Char);
+    }
     fn visit_string_expression(&mut self, ast: &mut Ast, _number: &StringExpr, expr: &Expr) {
         ast.set_type(expr.id, Type::String);
     }
 
+    fn visit_interpolated_string_expression(&mut self, ast: &mut Ast, interpolated_string: &InterpolatedStringExpr, expr: &Expr) {
+        for part in &interpolated_string.parts {
+            if let InterpolatedStringPart::Expr(part_expr) = part {
+                self.visit_expression(ast, *part_expr);
+                let part_expr = ast.query_expr(*part_expr);
+                if let Type::Void = part_expr.ty {
+                    self.diagnostics.borrow_mut().report_cannot_interpolate_type(&part_expr.span(ast), &part_expr.ty);
+                }
+            }
+        }
+        ast.set_type(expr.id, Type::String);
+    }
+
     fn visit_boolean_expression(&mut self, ast: &mut Ast, _boolean: &BoolExpr, expr: &Expr) {
         ast.set_type(expr.id, Type::Bool);
     }
@@ -479,9 +903,15 @@ impl ASTVisitor for Resolver {
     fn visit_error(&mut self, _ast: &mut Ast, _span: &TextSpan) {}
 
     fn visit_unary_expression(&mut self, ast: &mut Ast, unary_expression: &UnaryExpr, expr: &Expr) {
-        self.visit_expression(ast, unary_expression.operand);
+        let operand = ast.query_expr(unary_expression.operand).clone();
+        match (&unary_expression.operator.kind, &operand.kind) {
+            // `-128i8` is in range even though `128i8` isn't
+            (UnOpKind::Minus, ExprKind::Number(number)) => self.resolve_integer_literal(ast, number, &operand, true),
+            _ => self.visit_expression(ast, unary_expression.operand),
+        }
         let operand = ast.query_expr(unary_expression.operand);
-        let ty = self.resolve_unary_expression(ast, &operand, &unary_expression.operator.kind);
+        let (operator, ty) = self.resolve_unary_expression(ast, &operand, &unary_expression.operator);
+        ast.set_unary_operator(expr.id, operator);
         ast.set_type(expr.id, ty);
     }
 
@@ -491,7 +921,12 @@ impl ASTVisitor for Resolver {
         let left = ast.query_expr(binary_expression.left);
         let right = ast.query_expr(binary_expression.right);
 
-        let ty = self.resolve_binary_expression(ast, &left, &right, &binary_expression.operator.kind);
+        let (operator, ty) = self.resolve_binary_expression(
+            &binary_expression.operator,
+            (&left.ty, left.span(ast)),
+            (&right.ty, right.span(ast)),
+        );
+        ast.set_binary_operator(expr.id, operator);
         ast.set_type(expr.id, ty);
     }
 
@@ -508,72 +943,90 @@ pub struct CompilationUnit {
     pub ast: Ast,
     pub diagnostics_bag: DiagnosticsBagCell,
     pub global_scope: GlobalScope,
+    pub text: text::SourceText,
 }
 
 impl CompilationUnit {
     pub fn compile(input: &str) -> Result<CompilationUnit, DiagnosticsBagCell> {
         let text = text::SourceText::new(input.to_string());
-        let mut lexer = Lexer::new(input);
+        let diagnostics_bag: DiagnosticsBagCell = Rc::new(RefCell::new(diagnostics::DiagnosticsBag::new()));
+        let mut lexer = Lexer::new(input, Rc::clone(&diagnostics_bag));
         let mut tokens = Vec::new();
         while let Some(token) = lexer.next_token() {
             tokens.push(token);
         }
-        let diagnostics_bag: DiagnosticsBagCell = Rc::new(RefCell::new(diagnostics::DiagnosticsBag::new()));
         let mut ast = Ast::new();
-        let mut global_scope = GlobalScope::new();
         let mut parser = Parser::new(
             tokens,
             Rc::clone(&diagnostics_bag),
             &mut ast,
-            &mut global_scope,
         );
         parser.parse();
         // ast.visualize();  // to visualize the 
-        Self::check_diagnostics(&text, &diagnostics_bag).map_err(|_| Rc::clone(&diagnostics_bag))?;
-        let scopes = Scopes::from_global_scope(global_scope);
-        let mut resolver = Resolver::new(Rc::clone(&diagnostics_bag), scopes);
+        // The parser recovers from syntax errors, so the resolver still runs to report type errors in the same build
+        let mut resolver = Resolver::new(Rc::clone(&diagnostics_bag), Scopes::new());
         resolver.resolve(&mut ast);
         Self::check_diagnostics(&text, &diagnostics_bag).map_err(|_| Rc::clone(&diagnostics_bag))?;
+        Self::print_diagnostics(&text, &diagnostics_bag);
         Ok(CompilationUnit {
             global_scope: resolver.scopes.global_scope,
             ast,
             diagnostics_bag,
+            text,
         })
     }
 
 
     pub fn maybe_run(&mut self) {
-        if self.diagnostics_bag.borrow().diagnostics.len() > 0 {
+        if self.diagnostics_bag.borrow().has_errors() {
             return;
         }
         self.run();
     }
 
     pub fn run(&mut self) {
+        match self.evaluate() {
+            Ok(value) => println!("{:?}", value),
+            Err(error) => DiagnosticsPrinter::new(&self.text, &[error]).print(),
+        }
+    }
+
+    /// Evaluates the top-level statements, which initialize the globals, then `main` if there
+    /// is one, and returns the last value, or the runtime error that stopped the evaluation.
+    pub fn evaluate(&mut self) -> Result<Option<Value>, Diagnostic> {
         let mut eval = ASTEvaluator::new(
             &self.global_scope,
         );
-        let main_function_ref = self.global_scope.lookup_function("main");
-        if let Some(function) = main_function_ref {
+        self.ast.visit(&mut eval);
+        if let Some(error) = eval.take_error() {
+            return Err(error);
+        }
+        if let Some(function) = self.global_scope.lookup_function("main") {
             let function = self.global_scope.functions.get(function);
-            eval.visit_expression(&mut self.ast, function.body);
-        } else {
-            self.ast.visit(&mut eval);
+            eval.visit_function_body(&mut self.ast, function.body);
+        }
+        match eval.take_error() {
+            Some(error) => Err(error),
+            None => Ok(eval.last_value),
         }
-        println!("{:?}", eval.last_value);
     }
 
-
+    /// Prints all diagnostics and fails if any of them is an error. Warnings alone are
+    /// left for the final `print_diagnostics` so that they are only printed once.
     fn check_diagnostics(text: &text::SourceText, diagnostics_bag: &DiagnosticsBagCell) -> Result<(), ()> {
-        let diagnostics_binding = diagnostics_bag.borrow();
-        if diagnostics_binding.diagnostics.len() > 0 {
-            let diagnostics_printer = DiagnosticsPrinter::new(
-                &text,
-                &diagnostics_binding.diagnostics,
-            );
-            diagnostics_printer.print();
+        if diagnostics_bag.borrow().has_errors() {
+            Self::print_diagnostics(text, diagnostics_bag);
             return Err(());
         }
         Ok(())
     }
+
+    fn print_diagnostics(text: &text::SourceText, diagnostics_bag: &DiagnosticsBagCell) {
+        let diagnostics_binding = diagnostics_bag.borrow();
+        let diagnostics_printer = DiagnosticsPrinter::new(
+            text,
+            &diagnostics_binding.diagnostics,
+        );
+        diagnostics_printer.print();
+    }
 }
\ No newline at end of file
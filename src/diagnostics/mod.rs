@@ -48,10 +48,22 @@ impl DiagnosticsBag {
         self.diagnostics.push(warning);
     }
 
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| matches!(diagnostic.kind, DiagnosticKind::Error))
+    }
+
     pub fn report_unexpected_token(&mut self, expected: &TokenKind, token: &Token) {
+        if token.kind == TokenKind::Eof {
+            self.report_error(format!("Unexpected end of file, expected <{}>", expected), token.span.clone());
+            return;
+        }
         self.report_error(format!("Expected <{}>, found <{}>", expected, token.kind), token.span.clone());
     }
     pub fn report_expected_expression(&mut self, token: &Token) {
+        if token.kind == TokenKind::Eof {
+            self.report_error("Unexpected end of file, expected expression".to_string(), token.span.clone());
+            return;
+        }
         self.report_error(format!("Expected expression, found <{}>", token.kind), token.span.clone());
     }
 
@@ -63,8 +75,20 @@ impl DiagnosticsBag {
         self.report_error(format!("Undeclared function '{}'", token.span.literal), token.span.clone());
     }
 
-    pub fn report_undeclared_d_before_decimal(&mut self, token: &Token) {
-        self.report_error(format!("Expected d before the decimal value '{}'", token.span.literal), token.span.clone());
+    pub fn report_deprecated_decimal_prefix(&mut self, span: &TextSpan) {
+        self.report_warning(format!("The 'd' prefix on float literals is deprecated, write '{}' instead", &span.literal[1..]), span.clone());
+    }
+
+    pub fn report_chained_comparison(&mut self, span: &TextSpan) {
+        self.report_error("Comparison operators cannot be chained, use '&&' to combine comparisons".to_string(), span.clone());
+    }
+
+    pub fn report_mixed_script_identifier(&mut self, span: &TextSpan) {
+        self.report_warning(format!("Identifier '{}' mixes characters from several scripts", span.literal), span.clone());
+    }
+
+    pub fn report_confusable_identifier(&mut self, span: &TextSpan, other: &str) {
+        self.report_warning(format!("Identifier '{}' is confusable with '{}'", span.literal, other), span.clone());
     }
 
     pub fn report_cannot_call_no_callable_expression(&mut self, callee_span: &TextSpan, callee_type: &Type) {
@@ -79,18 +103,114 @@ impl DiagnosticsBag {
         self.report_error(format!("Function '{}' already declared", token.span.literal), token.span.clone());
     }
 
+    pub fn report_global_used_before_initialization(&mut self, span: &TextSpan, function: &str, global: &str) {
+        self.report_error(format!("Cannot call '{}' before the global '{}' it uses is initialized", function, global), span.clone());
+    }
+
     pub fn report_type_mismatch(&mut self, span: &TextSpan, expected: &Type, actual: &Type) {
-        self.report_error(format!("Expected type '{}', found '{}'", expected, actual), span.clone());
+        let message = match (expected, actual) {
+            // Numbers are never converted implicitly, suggest the cast
+            (expected, actual) if expected.is_numeric() && actual.is_numeric() => {
+                format!("Expected type '{}', found '{}', convert it with 'as {}'", expected, actual, expected)
+            }
+            _ => format!("Expected type '{}', found '{}'", expected, actual),
+        };
+        self.report_error(message, span.clone());
+    }
+
+    pub fn report_invalid_cast(&mut self, span: &TextSpan, from: &Type, to: &Type) {
+        let message = match (from, to) {
+            (Type::String, Type::Int) => "Cannot cast 'string' to 'int', use 'parse_int' instead".to_string(),
+            (Type::String, Type::Float) => "Cannot cast 'string' to 'float', use 'parse_float' instead".to_string(),
+            _ => format!("Cannot cast '{}' to '{}'", from, to),
+        };
+        self.report_error(message, span.clone());
     }
 
     pub fn report_undeclared_type(&mut self, token: &Token) {
         self.report_error(format!("Undeclared type '{}'", token.span.literal), token.span.clone());
     }
 
+    pub fn report_negated_unsigned_type(&mut self, operator: &Token, ty: &Type) {
+        self.report_error(format!("Operator '{}' cannot be applied to the unsigned type '{}'", operator.span.literal, ty), operator.span.clone());
+    }
+
+    pub fn report_mismatched_operand_types(&mut self, operator: &Token, left: &Type, right: &Type) {
+        self.report_error(format!("Operator '{}' cannot be applied to '{}' and '{}', convert one operand with 'as'", operator.span.literal, left, right), operator.span.clone());
+    }
+
     pub fn report_cannot_return_outside_function(&mut self, token: &Token) {
         self.report_error(format!("Cannot use 'return' outside of function"), token.span.clone());
     }
 
+    pub fn report_not_all_code_paths_return_value(&mut self, function_name: &Token) {
+        self.report_error("Not all code paths return a value".to_string(), function_name.span.clone());
+    }
+
+    pub fn report_unreachable_code(&mut self, span: &TextSpan) {
+        self.report_warning("Unreachable code".to_string(), span.clone());
+    }
+
+    pub fn report_cannot_use_outside_of_loop(&mut self, token: &Token) {
+        self.report_error(format!("Cannot use '{}' outside of loop", token.span.literal), token.span.clone());
+    }
+
+    pub fn report_unknown_label(&mut self, token: &Token) {
+        self.report_error(format!("Unknown label {}", token.span.literal), token.span.clone());
+    }
+
+    pub fn report_break_value_outside_of_loop_expression(&mut self, span: &TextSpan) {
+        self.report_error("Only 'loop' can be exited with a value".to_string(), span.clone());
+    }
+
+    pub fn report_unterminated_block_comment(&mut self, span: &TextSpan) {
+        self.report_error("Unterminated block comment".to_string(), span.clone());
+    }
+
+    pub fn report_unterminated_string(&mut self, span: &TextSpan) {
+        self.report_error("Unterminated string literal".to_string(), span.clone());
+    }
+
+    pub fn report_unterminated_char(&mut self, span: &TextSpan) {
+        self.report_error("Unterminated character literal".to_string(), span.clone());
+    }
+
+    pub fn report_invalid_char_literal(&mut self, span: &TextSpan) {
+        self.report_error("Character literals must contain exactly one character, use double quotes for strings".to_string(), span.clone());
+    }
+
+    pub fn report_unknown_escape_sequence(&mut self, span: &TextSpan) {
+        self.report_error(format!("Unknown escape sequence '{}'", span.literal), span.clone());
+    }
+
+    pub fn report_invalid_unicode_escape(&mut self, span: &TextSpan) {
+        self.report_error(format!("Invalid unicode escape '{}'", span.literal), span.clone());
+    }
+
+    pub fn report_unterminated_interpolation(&mut self, span: &TextSpan) {
+        self.report_error("Unterminated string interpolation".to_string(), span.clone());
+    }
+
+    pub fn report_cannot_interpolate_type(&mut self, span: &TextSpan, ty: &Type) {
+        self.report_error(format!("Cannot interpolate value of type '{}'", ty), span.clone());
+    }
+
+    pub fn report_literal_out_of_range(&mut self, span: &TextSpan, ty: &Type) {
+        self.report_error(format!("Literal out of range for type '{}'", ty), span.clone());
+    }
+
+    pub fn report_unknown_integer_suffix(&mut self, span: &TextSpan) {
+        self.report_error(format!("Unknown integer suffix '{}', expected one like 'i32' or 'u8'", span.literal), span.clone());
+    }
+
+    pub fn report_invalid_digit(&mut self, span: &TextSpan, radix: u32) {
+        self.report_error(format!("Invalid digit '{}' for a base {} literal", span.literal, radix), span.clone());
+    }
+
+    pub fn report_missing_digits(&mut self, span: &TextSpan) {
+        self.report_error(format!("Expected digits after '{}'", span.literal), span.clone());
+    }
+
     pub fn report_cannot_use_rec_outside_of_function(&mut self, token: &Token) {
         self.report_error(format!("Cannot use 'rec' outside of function"), token.span.clone());
     }
@@ -120,7 +240,7 @@ mod test {
             let raw = Self::get_raw_text(input);
             let compilation_unit = CompilationUnit::compile(&raw);
             match compilation_unit {
-                Ok(_) => vec![],
+                Ok(compilation_unit) => compilation_unit.diagnostics_bag.borrow().diagnostics.clone(),
                 Err(e) => e.borrow().diagnostics.clone(),
             }
         }
@@ -150,7 +270,7 @@ mod test {
                         diagnostics.push(diagnostic);
                     }
                     _ => {
-                        current_position += 1;
+                        current_position += c.len_utf8();
                     }
                 };
             }
@@ -217,12 +337,13 @@ mod test {
             let c = 10
         }
          else
-            a = 5
+            «a = 5»
         a
 b
 «c»
     ";
         let expected = vec![
+            "Expected type 'void', found 'int'",
             "Undeclared variable 'c'"
         ];
 
@@ -577,5 +698,668 @@ b
 
         assert_diagnostics(input, expected);
     }
-}
 
+    #[test]
+    pub fn should_report_unterminated_block_comment() {
+        let input = "\
+        let a = 1
+        «/*» let b = 2 /* nested */
+        ";
+
+        let expected = vec![
+            "Unterminated block comment"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_unknown_escape_sequence() {
+        let input = r#"let a = "a «\q» b""#;
+
+        let expected = vec![
+            "Unknown escape sequence '\\q'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_unicode_escape() {
+        let input = r#"let a = "«\u{110000}»""#;
+
+        let expected = vec![
+            "Invalid unicode escape '\\u{110000}'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_unterminated_string() {
+        let input = "let a = «\"»abc";
+
+        let expected = vec![
+            "Unterminated string literal"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_interpolation_of_void_value() {
+        let input = r#"
+        func a {}
+        let b = "value: ${«a()»}"
+        "#;
+
+        let expected = vec![
+            "Cannot interpolate value of type 'void'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_unterminated_interpolation() {
+        let input = r#"let a = "value: «${»1 + 2"#;
+
+        let expected = vec![
+            "Unterminated string interpolation",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_unterminated_indented_string() {
+        let input = "let a = «''»\n  abc '\n";
+
+        let expected = vec![
+            "Unterminated string literal"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_integer_literal_out_of_range() {
+        let input = "let a = «9_223_372_036_854_775_808»";
+
+        let expected = vec![
+            "Literal out of range for type 'int'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_hex_literal_out_of_range() {
+        let input = "let a = «0x1_0000_0000_0000_0000»";
+
+        let expected = vec![
+            "Literal out of range for type 'int'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_float_literal_out_of_range() {
+        let input = "let a = «1e400»";
+
+        let expected = vec![
+            "Literal out of range for type 'float'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_digit_in_binary_literal() {
+        let input = "let a = 0b1«2»1";
+
+        let expected = vec![
+            "Invalid digit '2' for a base 2 literal"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_missing_digits_after_prefix() {
+        let input = "let a = «0x»";
+
+        let expected = vec![
+            "Expected digits after '0x'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_warn_about_deprecated_decimal_prefix() {
+        let input = "let a = «d56.2»";
+
+        let expected = vec![
+            "The 'd' prefix on float literals is deprecated, write '56.2' instead"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_diagnostics_at_byte_offsets_in_non_ascii_source() {
+        let input = "let größe = \"日本\"\nlet a = «ünknown»";
+
+        let expected = vec![
+            "Undeclared variable 'ünknown'"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_warn_about_mixed_script_identifier() {
+        // The 'а' is Cyrillic
+        let input = "let «pаypal» = 1";
+
+        let expected = vec![
+            "Identifier 'pаypal' mixes characters from several scripts"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_warn_about_confusable_identifier() {
+        // The 'о' is Cyrillic
+        let input = "let foo = 1\nlet ««fоo»» = foo";
+
+        let expected = vec![
+            "Identifier 'fоo' mixes characters from several scripts",
+            "Identifier 'fоo' is confusable with 'foo'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_missing_closing_brace_at_end_of_file() {
+        let input = "\
+        func add(a: int, b: int) -> int {
+            return a + b
+        «»";
+
+        let expected = vec![
+            "Unexpected end of file, expected <}>"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_missing_closing_parenthesis_at_end_of_file() {
+        let input = "let a = (1 + 2«»";
+
+        let expected = vec![
+            "Unexpected end of file, expected <)>"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_missing_expression_at_end_of_file() {
+        let input = "let a = «»";
+
+        let expected = vec![
+            "Unexpected end of file, expected expression"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_type_errors_after_syntax_errors() {
+        let input = "\
+        let a = (1 + 2
+        «let» b: bool = «a»
+        ";
+
+        let expected = vec![
+            "Expected <)>, found <Let>",
+            "Expected type 'bool', found 'int'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_only_the_first_syntax_error_of_a_statement() {
+        let input = "\
+        let x = 1 + «*» 2 * ) ;
+        let y = «z»
+        ";
+
+        let expected = vec![
+            "Expected expression, found <*>",
+            "Undeclared variable 'z'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_skip_function_without_name() {
+        let input = "\
+        func «(»a: int) {
+            let b = a
+        }
+        func f() -> int {
+            return «true»
+        }
+        ";
+
+        let expected = vec![
+            "Expected <Identifier>, found <(>",
+            "Expected type 'int', found 'bool'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_recover_from_let_without_name_inside_block() {
+        let input = "\
+        func f() {
+            let «=» 5
+            let c: int = «\"s\"»
+        }
+        ";
+
+        let expected = vec![
+            "Expected <Identifier>, found <=>",
+            "Expected type 'int', found 'string'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_non_bool_operands_of_logical_operators() {
+        let input = "let a = !«1» && «2»";
+
+        let expected = vec![
+            "Expected type 'bool', found 'int'",
+            "Expected type 'bool', found 'int'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_non_int_operands_of_modulo_and_shift_operators() {
+        let input = "let a = «true» % 2 >>> «\"1\"»";
+
+        let expected = vec![
+            "Expected type 'int', found 'bool'",
+            "Expected type 'int', found 'string'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_type_check_compound_assignment_like_the_binary_operator() {
+        let input = "\
+        let a = \"text\"
+        «a» -= 1
+        let b = 1
+        b |= «true»
+        ";
+
+        let expected = vec![
+            "Expected type 'int', found 'string'",
+            "Expected type 'int', found 'bool'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_scope_the_for_loop_variable_to_the_body() {
+        let input = "\
+        for i in 0..«true» step «\"2\"» {
+            i
+        }
+        «i»
+        ";
+
+        let expected = vec![
+            "Expected type 'int', found 'bool'",
+            "Expected type 'int', found 'string'",
+            "Undeclared variable 'i'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_chained_comparison() {
+        let input = "let a = 1 < 2 «<» 3";
+
+        let expected = vec![
+            "Comparison operators cannot be chained, use '&&' to combine comparisons"
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_break_and_continue() {
+        let input = "\
+        «break»
+        'outer: while true {
+            continue «'inner»
+            break «1»
+        }
+        ";
+
+        let expected = vec![
+            "Cannot use 'break' outside of loop",
+            "Unknown label 'inner",
+            "Only 'loop' can be exited with a value",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_mismatched_break_values() {
+        let input = "\
+        let a = loop {
+            if true {
+                break 1
+            }
+            break «false»
+        }
+        ";
+
+        let expected = vec![
+            "Expected type 'int', found 'bool'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_not_all_code_paths_return_a_value() {
+        let input = "\
+        func «positive»(a: int) -> int {
+            if a > 0 {
+                return 1
+            }
+        }
+        func sign(a: int) -> int {
+            if a < 0 {
+                return -1
+            } else {
+                return 1
+            }
+        }
+        func forever() -> int {
+            while true {}
+        }
+        func «until»(a: int) -> int {
+            loop {
+                if a > 0 {
+                    break
+                }
+                return 1
+            }
+        }
+        ";
+
+        let expected = vec![
+            "Not all code paths return a value",
+            "Not all code paths return a value",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_warn_about_unreachable_statements() {
+        let input = "\
+        func f() -> int {
+            return 1
+            «f() + 1»
+        }
+        func g() {
+            for i in 0..3 {
+                continue
+                «g()»
+            }
+            return;
+        }
+        ";
+
+        let expected = vec![
+            "Unreachable code",
+            "Unreachable code",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_resolve_functions_and_globals_declared_later() {
+        let input = "\
+        func is_even(n: int) -> bool {
+            if n == 0 {
+                return true
+            }
+            is_odd(n - 1)
+        }
+        func scaled(n: int) -> int {
+            n * «factor»
+        }
+        func is_odd(n: int) -> bool {
+            if n == 0 {
+                return false
+            }
+            is_even(«n == 1»)
+        }
+        let factor = true
+        ";
+
+        let expected = vec![
+            "Expected type 'int', found 'bool'",
+            "Expected type 'int', found 'bool'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_calls_using_globals_before_they_are_initialized() {
+        let input = "\
+        func f() -> int {
+            return g
+        }
+        func twice() -> int {
+            f() + f()
+        }
+        func main() -> int {
+            f()
+        }
+        let x = «twice»()
+        let y = «f»()
+        let g = 5
+        let z = f()
+        ";
+
+        let expected = vec![
+            "Cannot call 'twice' before the global 'g' it uses is initialized",
+            "Cannot call 'f' before the global 'g' it uses is initialized",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_mixed_int_and_float_operands() {
+        let input = "\
+        let a = 1.5
+        let b = a «*» 2
+        let c = a < 2.5
+        let d: float = «3»
+        a «+=» 1
+        ";
+
+        let expected = vec![
+            "Operator '*' cannot be applied to 'float' and 'int', convert one operand with 'as'",
+            "Expected type 'float', found 'int', convert it with 'as float'",
+            "Operator '+=' cannot be applied to 'float' and 'int', convert one operand with 'as'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_casts() {
+        let input = "\
+        let a = «\"1\" as int»
+        let b = «true as float»
+        let c = 1.5 as int as bool as int as string
+        ";
+
+        let expected = vec![
+            "Cannot cast 'string' to 'int', use 'parse_int' instead",
+            "Cannot cast 'bool' to 'float'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_string_operations() {
+        let input = "\
+        let a = «42»[0]
+        let b = \"text\"[«1.5»..2]
+        let c = \"text\" «<» 1
+        let d = len(«7»)
+        ";
+
+        let expected = vec![
+            "Expected type 'string', found 'int'",
+            "Expected type 'int', found 'float', convert it with 'as int'",
+            "Operator '<' cannot be applied to 'string' and 'int', convert one operand with 'as'",
+            "Expected type 'string', found 'int'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_char_literals() {
+        let input = "\
+        let a = «'ab'»
+        let b = «'»
+        ";
+
+        let expected = vec![
+            "Character literals must contain exactly one character, use double quotes for strings",
+            "Unterminated character literal",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_invalid_char_operations() {
+        let input = "\
+        let a = 'a' «<» \"b\"
+        for c in «3» {}
+        let d = «'d'» + 1
+        ";
+
+        let expected = vec![
+            "Operator '<' cannot be applied to 'char' and 'string', convert one operand with 'as'",
+            "Expected type 'string', found 'int'",
+            "Expected type 'int', found 'char'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_integer_literals_out_of_range() {
+        let input = "\
+        let a = «256u8»
+        let b = -128i8 + -«129i8»
+        let c = «9223372036854775808»
+        let d = -9223372036854775808 + «-»1u8 as int
+        let e = «-»1u8
+        let f = 5u32
+        let g = «-»f + 1u32
+        ";
+
+        let expected = vec![
+            "Literal out of range for type 'u8'",
+            "Literal out of range for type 'i8'",
+            "Literal out of range for type 'int'",
+            "Operator '-' cannot be applied to the unsigned type 'u8'",
+            "Operator '-' cannot be applied to the unsigned type 'u8'",
+            "Operator '-' cannot be applied to the unsigned type 'u32'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_ranges_and_slices_with_mixed_integer_types() {
+        let input = "\
+        for i in 0u8..«3» {}
+        let s = \"abc\"[1usize..«2u8»]
+        let c = \"abc\"[«1.0»]
+        ";
+
+        let expected = vec![
+            "Expected type 'u8', found 'int', convert it with 'as u8'",
+            "Expected type 'usize', found 'u8', convert it with 'as usize'",
+            "Expected type 'int', found 'float', convert it with 'as int'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_report_unknown_integer_suffix() {
+        let input = "let a = 5«u7»";
+
+        let expected = vec![
+            "Unknown integer suffix 'u7', expected one like 'i32' or 'u8'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+
+    #[test]
+    pub fn should_not_mix_integer_types() {
+        let input = "\
+        let a: i32 = «5»
+        let b = 1u8 «+» 1
+        let c = 1u16 «<» 2u32
+        ";
+
+        let expected = vec![
+            "Expected type 'i32', found 'int', convert it with 'as i32'",
+            "Operator '+' cannot be applied to 'u8' and 'int', convert one operand with 'as'",
+            "Operator '<' cannot be applied to 'u16' and 'u32', convert one operand with 'as'",
+        ];
+
+        assert_diagnostics(input, expected);
+    }
+}
@@ -1,6 +1,6 @@
 use std::cmp;
-use termion::color::{Fg, Red, Reset};
-use crate::diagnostics::Diagnostic;
+use termion::color::{Color, Fg, Red, Reset, Yellow};
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
 use crate::text::SourceText;
 
 pub struct DiagnosticsPrinter<'a> {
@@ -30,44 +30,60 @@ impl <'a> DiagnosticsPrinter<'a> {
     /// 
     /// 
     pub fn stringify_diagnostic(&self, diagnostic: &Diagnostic) -> String {
-        let line_index = self.text.line_index(diagnostic.span.start);
+        // Diagnostics at the end of file point just after the last line with content
+        let start = if diagnostic.span.start >= self.text.len() {
+            self.text.content_end()
+        } else {
+            diagnostic.span.start
+        };
+        let line_index = self.text.line_index(start);
         let line = self.text.get_line(line_index);
         let line_start = self.text.line_start(line_index);
 
-        let column = diagnostic.span.start - line_start;
+        let offset = start - line_start;
+        let column = line[..offset].chars().count();
 
-        let (prefix, span, suffix) = self.get_text_spans(diagnostic, &line, column);
+        let (prefix, span, suffix) = Self::get_text_spans(diagnostic, line, offset);
 
-        let indent = cmp::min(PREFIX_LENGTH, column);
-        let (arrow_pointers, arrow_line) = Self::format_arrow(diagnostic, indent);
+        let indent = prefix.chars().count();
+        let (arrow_pointers, arrow_line) = Self::format_arrow(span, indent);
         let error_message = Self::format_error_message(diagnostic, indent, column, line_index);
-        format!("{}{}{}{}{}\n{}\n{}\n{}", prefix, Fg(Red), span, Fg(Reset), suffix, arrow_pointers, arrow_line, error_message)
+        let color: &dyn Color = match diagnostic.kind {
+            DiagnosticKind::Error => &Red,
+            DiagnosticKind::Warning => &Yellow,
+        };
+        format!("{}{}{}{}{}\n{}\n{}\n{}", prefix, Fg(color), span, Fg(Reset), suffix, arrow_pointers, arrow_line, error_message)
     }
 
     fn format_error_message(diagnostic: &Diagnostic, indent: usize, column: usize, line_index: usize) -> String {
         format!("{:indent$}+-- {} ({}:{})", "", diagnostic.message, column + 1, line_index + 1, indent = indent)
     }
 
-    fn format_arrow(diagnostic: &Diagnostic, indent: usize) -> (String, String) {
-        let arrow_pointers = format!("{:indent$}{}", "", std::iter::repeat(
-            '^'
-        ).take(
-            diagnostic.span.length()
-        ).collect::<String>(), indent = indent);
+    fn format_arrow(span: &str, indent: usize) -> (String, String) {
+        let arrow_pointers = format!("{:indent$}{}", "", "^".repeat(cmp::max(1, span.chars().count())), indent = indent);
         let arrow_line = format!("{:indent$}|", "", indent = indent);
         (arrow_pointers, arrow_line)
     }
 
-    fn get_text_spans(&'a self, diagnostic: &Diagnostic, line: &'a str, column: usize) -> (&'a str, &'a str, &'a str) {
-        let prefix_start = cmp::max(0, column as isize - PREFIX_LENGTH as isize) as usize;
-        let prefix_end = column;
-        let suffix_start = cmp::min(column + diagnostic.span.length(), line.len());
-        let suffix_end = cmp::min(suffix_start + PREFIX_LENGTH, line.len());
+    /// Splits the line around the diagnostic's span, keeping up to `PREFIX_LENGTH`
+    /// characters of context on each side. `offset` is the byte offset of the span
+    /// within the line.
+    fn get_text_spans(diagnostic: &Diagnostic, line: &'a str, offset: usize) -> (&'a str, &'a str, &'a str) {
+        let (before, rest) = line.split_at(offset);
+        let span_end = Self::floor_char_boundary(rest, diagnostic.span.length());
+        let (span, after) = rest.split_at(span_end);
 
-        let prefix = &line[prefix_start..prefix_end];
-        let span = &line[prefix_end..suffix_start];
-        let suffix = &line[suffix_start..suffix_end];
-        (prefix, span, suffix)
+        let prefix_start = before.char_indices().rev().nth(PREFIX_LENGTH - 1).map_or(0, |(index, _)| index);
+        let suffix_end = after.char_indices().nth(PREFIX_LENGTH).map_or(after.len(), |(index, _)| index);
+        (&before[prefix_start..], span, &after[..suffix_end])
+    }
+
+    fn floor_char_boundary(text: &str, index: usize) -> usize {
+        let mut index = cmp::min(index, text.len());
+        while !text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
     }
 
     pub fn print(&self) {
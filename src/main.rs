@@ -55,7 +55,7 @@ fn main() -> Result<(), ()> {
     } 
     let mut compilation_unit = CompilationUnit::compile(&input).map_err(|_| ())?;
     compilation_unit.run();
-    let c_transpiler = CTranspiler::new(&compilation_unit.global_scope);
+    let c_transpiler = CTranspiler::new(&compilation_unit.global_scope, &compilation_unit.text);
     let _transpiled_code = c_transpiler.transpile(&mut compilation_unit.ast);
     // println!("{}", transpiled_code);
     // let mut c_file = File::create("out.c").unwrap();
@@ -11,15 +11,32 @@ impl SourceText {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// The byte offset just after the last non-whitespace character.
+    pub fn content_end(&self) -> usize {
+        self.text.trim_end().len()
+    }
+
+    /// The index of the line containing the byte `position`.
     pub fn line_index(&self, position: usize) -> usize {
-        self.text[..=position].lines().count() - 1
+        self.text[..position].matches('\n').count()
     }
 
     pub fn get_line(&self, index: usize) -> &str {
-        self.text.lines().nth(index).unwrap()
+        let start = self.line_start(index);
+        let line = &self.text[start..];
+        let line = line.split('\n').next().unwrap();
+        line.strip_suffix('\r').unwrap_or(line)
     }
 
+    /// The byte offset at which the line `index` starts.
     pub fn line_start(&self, index: usize) -> usize {
-        self.text.lines().take(index).map(|line| line.len() + 1).sum()
+        if index == 0 {
+            return 0;
+        }
+        self.text.match_indices('\n').nth(index - 1).map(|(newline, _)| newline + 1).unwrap()
     }
 }
@@ -2,9 +2,13 @@ use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone)]
 pub enum Type {
+    /// The default integer type, `i64`
     Int,
+    /// The sized integer types other than `i64`, use `Type::integer` to construct them
+    Integer(IntegerType),
     Bool,
     Float,
+    Char,
     String,
     // Null,
     Void,
@@ -16,8 +20,10 @@ impl Display for Type {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let type_name = match self {
             Type::Int => "int",
+            Type::Integer(integer) => integer.name(),
             Type::Bool => "bool",
             Type::Float => "float",
+            Type::Char => "char",
             Type::String => "string",
             // Type::Null => "null",
             Type::Unresolved => "unresolved",
@@ -30,29 +36,154 @@ impl Display for Type {
 }
 
 impl Type {
+    /// The type of an integer, `i64` is `int`.
+    pub fn integer(integer: IntegerType) -> Type {
+        match integer {
+            IntegerType::I64 => Type::Int,
+            integer => Type::Integer(integer),
+        }
+    }
+
+    /// All the integer types, `int` first.
+    pub fn integers() -> impl Iterator<Item = Type> {
+        IntegerType::ALL.into_iter().map(Type::integer)
+    }
+
+    pub fn integer_type(&self) -> Option<IntegerType> {
+        match self {
+            Type::Int => Some(IntegerType::I64),
+            Type::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Float) || self.integer_type().is_some()
+    }
+
+    /// There are no implicit conversions, not even between `int` and `float` or two
+    /// integer types.
     pub fn is_assignable_to(&self, other: &Type) -> bool {
         match (self, other) {
-            (Type::Int, Type::Int) => true,
+            (Type::Int | Type::Integer(_), Type::Int | Type::Integer(_)) => self.integer_type() == other.integer_type(),
             (Type::Float, Type::Float) => true,
-            (Type::Float, Type::Int) => true,
-            (Type::Int, Type::Float) => true,
+            (Type::Char, Type::Char) => true,
             (Type::String, Type::String) => true,
             (Type::String, Type::Int) => false,
             (Type::Bool, Type::Bool) => true,
+            (Type::Void, Type::Void) => true,
             (Type::Error, _) => true,
             (_, Type::Error) => true,
             _ => false,
         }
     }
 
+    /// The conversions `as` supports. Casts between integer types keep the low bits of
+    /// the value, like C does. Casts from floats to integer types truncate toward zero,
+    /// saturate at the bounds of the type and turn NaN into 0. Casts from chars are the
+    /// code point, saturated the same way, and casts to chars turn values that aren't
+    /// one into U+FFFD. Strings are converted to numbers with `parse_int` and
+    /// `parse_float` instead, since that can fail.
+    pub fn is_castable_to(&self, other: &Type) -> bool {
+        matches!(
+            (self, other),
+            (Type::Error, _)
+                | (_, Type::Error)
+                | (Type::Int | Type::Integer(_), Type::Int | Type::Integer(_) | Type::Float | Type::Bool | Type::Char | Type::String)
+                | (Type::Float, Type::Float | Type::Int | Type::Integer(_) | Type::String)
+                | (Type::Char, Type::Char | Type::Int | Type::Integer(_) | Type::String)
+                | (Type::Bool, Type::Bool | Type::Int | Type::Integer(_))
+                | (Type::String, Type::String)
+        )
+    }
+
     pub fn from_str(s: &str) -> Option<Type> {
         match s {
             "int" => Some(Type::Int),
             "float" => Some(Type::Float),
+            "char" => Some(Type::Char),
             "string" => Some(Type::String),
             "bool" => Some(Type::Bool),
             "void" => Some(Type::Void),
-            _ => None,
+            _ => IntegerType::from_name(s).map(Type::integer),
+        }
+    }
+}
+
+/// The sized integer types. Each has the range and the overflow behavior of its
+/// `<stdint.h>` counterpart: unsigned arithmetic wraps around, signed arithmetic that
+/// overflows is a runtime error where C would be undefined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    /// As wide as a pointer on the target, `size_t` in C
+    Usize,
+}
+
+impl IntegerType {
+    pub const ALL: [IntegerType; 9] = [
+        IntegerType::I64,
+        IntegerType::I8,
+        IntegerType::I16,
+        IntegerType::I32,
+        IntegerType::U8,
+        IntegerType::U16,
+        IntegerType::U32,
+        IntegerType::U64,
+        IntegerType::Usize,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegerType::I8 => "i8",
+            IntegerType::I16 => "i16",
+            IntegerType::I32 => "i32",
+            IntegerType::I64 => "i64",
+            IntegerType::U8 => "u8",
+            IntegerType::U16 => "u16",
+            IntegerType::U32 => "u32",
+            IntegerType::U64 => "u64",
+            IntegerType::Usize => "usize",
+        }
+    }
+
+    /// Also used for the suffixes of integer literals, like `255u8`.
+    pub fn from_name(name: &str) -> Option<IntegerType> {
+        IntegerType::ALL.into_iter().find(|integer| integer.name() == name)
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            IntegerType::I8 | IntegerType::U8 => 8,
+            IntegerType::I16 | IntegerType::U16 => 16,
+            IntegerType::I32 | IntegerType::U32 => 32,
+            IntegerType::I64 | IntegerType::U64 => 64,
+            IntegerType::Usize => usize::BITS,
         }
     }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, IntegerType::I8 | IntegerType::I16 | IntegerType::I32 | IntegerType::I64)
+    }
+
+    pub fn min(&self) -> i128 {
+        if self.is_signed() { -(1 << (self.bits() - 1)) } else { 0 }
+    }
+
+    pub fn max(&self) -> i128 {
+        if self.is_signed() { (1 << (self.bits() - 1)) - 1 } else { (1 << self.bits()) - 1 }
+    }
+
+    /// Wraps `value` into the range of the type by keeping its low bits, like a C cast.
+    pub fn wrap(&self, value: i128) -> i128 {
+        let value = value & ((1 << self.bits()) - 1);
+        if self.is_signed() && value > self.max() { value - (1 << self.bits()) } else { value }
+    }
 }